@@ -1,4 +1,22 @@
+// 默认画布大小，包含左右两边的墙和底部
 pub const CANVAS_WIDTH:i32=12;
 pub const CANVAS_HEIGHT:i32=22;
-pub const INIT_COORDINATE_X:i32 = CANVAS_WIDTH / 2;
-pub const INIT_COORDINATE_Y:i32 = -1;
\ No newline at end of file
+
+pub const MIN_BOARD_WIDTH:usize = 4;
+pub const MAX_BOARD_WIDTH:usize = 40;
+pub const MIN_BOARD_HEIGHT:usize = 4;
+pub const MAX_BOARD_HEIGHT:usize = 60;
+pub const HIDDEN_ROWS:usize = 2;
+pub const MIN_HIDDEN_ROWS:usize = 2;
+pub const MAX_HIDDEN_ROWS:usize = 20;
+pub const MIN_PREVIEW:usize = 1;
+pub const MAX_PREVIEW:usize = 6;
+
+pub const LINES_PER_LEVEL:u32 = 10;
+pub const MIN_START_LEVEL:u32 = 1;
+pub const MAX_START_LEVEL:u32 = 20;
+
+/// How often front ends advance the game clock, in milliseconds.
+pub const FRAME_MILLIS:u64 = 16;
+/// How long the final board of a timed-out game stays up before the results.
+pub const TIME_UP_MILLIS:u64 = 1000;
@@ -1,14 +1,12 @@
-use std::fmt::Debug;
-
-#[derive(Debug,Clone)]
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Default)]
 pub enum Color{
+    #[default]
     Red = 1,
     White = 2,
-    Green = 4
+    Green = 4,
+    Cyan = 8,
+    Yellow = 16,
+    Purple = 32,
+    Blue = 64,
+    Orange = 128
 }
-
-impl Default for Color {
-    fn default() -> Self {
-        Color::Red
-    }
-}
\ No newline at end of file
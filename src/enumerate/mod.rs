@@ -1 +1,3 @@
-mod color;include!("color.rs");
\ No newline at end of file
+mod color;
+
+pub use color::Color;
@@ -0,0 +1,57 @@
+use std::time::Duration;
+use crate::constant::constant;
+use crate::game::{GameMode, GravityCurve};
+use crate::model::Randomizer;
+
+/// Settings a [`crate::game::Game`] is started with.
+#[derive(Debug,Clone)]
+pub struct GameConfig{
+    pub mode:GameMode,
+    /// Playfield columns, walls excluded.
+    pub width:usize,
+    /// Visible playfield rows, floor excluded.
+    pub height:usize,
+    /// Rows of vanish zone above the visible field, where pieces spawn.
+    pub hidden:usize,
+    /// How many upcoming pieces are shown, clamped to `MIN_PREVIEW ..= MAX_PREVIEW`.
+    pub preview:usize,
+    pub randomizer:Randomizer,
+    pub seed:u64,
+    pub start_level:u32,
+    pub gravity:GravityCurve,
+    /// How long a grounded piece may sit before it locks.
+    pub lock_delay:Duration,
+    /// How many moves or rotations may restart the lock delay.
+    pub move_reset_limit:u32
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig{
+            mode:GameMode::default(),
+            width:(constant::CANVAS_WIDTH - 2) as usize,
+            height:(constant::CANVAS_HEIGHT - 1) as usize,
+            hidden:constant::HIDDEN_ROWS,
+            preview:5,
+            randomizer:Randomizer::default(),
+            seed:rand::random(),
+            start_level:1,
+            gravity:GravityCurve::default(),
+            lock_delay:Duration::from_millis(500),
+            move_reset_limit:15
+        }
+    }
+}
+
+impl GameConfig {
+    /// Whether the board is the default one: same size, vanish zone, preview and
+    /// randomizer. Only such games are comparable in the high score tables.
+    pub fn standard_board(&self) -> bool {
+        let standard = GameConfig::default();
+        self.width.clamp(constant::MIN_BOARD_WIDTH, constant::MAX_BOARD_WIDTH) == standard.width
+            && self.height.clamp(constant::MIN_BOARD_HEIGHT, constant::MAX_BOARD_HEIGHT) == standard.height
+            && self.hidden.clamp(constant::MIN_HIDDEN_ROWS, constant::MAX_HIDDEN_ROWS) == standard.hidden
+            && self.preview.clamp(constant::MIN_PREVIEW, constant::MAX_PREVIEW) == standard.preview
+            && self.randomizer == standard.randomizer
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+/// How long a piece takes to fall one row at each level.
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Default)]
+pub enum GravityCurve{
+    /// `(0.8 - (level - 1) * 0.007) ^ (level - 1)` seconds per row, 20G from level 20.
+    #[default]
+    Guideline,
+    /// The NTSC NES frame table, with level 1 here being NES level 0.
+    Nes
+}
+
+const NES_FRAME:Duration = Duration::from_nanos(16_639_267);
+const TWENTY_G_LEVEL:u32 = 20;
+
+impl GravityCurve {
+    /// Time per row at `level`. [`Duration::ZERO`] means 20G: the piece lands
+    /// as soon as it appears.
+    pub fn interval(&self,level:u32) -> Duration {
+        let level = level.max(1);
+        match self {
+            GravityCurve::Guideline => {
+                if level >= TWENTY_G_LEVEL {
+                    return Duration::ZERO;
+                }
+                let n = (level - 1) as f64;
+                Duration::from_secs_f64((0.8 - n * 0.007).powf(n))
+            }
+            GravityCurve::Nes => {
+                let frames = match level - 1 {
+                    0 => 48,
+                    1 => 43,
+                    2 => 38,
+                    3 => 33,
+                    4 => 28,
+                    5 => 23,
+                    6 => 18,
+                    7 => 13,
+                    8 => 8,
+                    9 => 6,
+                    10 ..= 12 => 5,
+                    13 ..= 15 => 4,
+                    16 ..= 18 => 3,
+                    19 ..= 28 => 2,
+                    _ => 1,
+                };
+                NES_FRAME * frames
+            }
+        }
+    }
+}
+
+/// Accumulates time towards the next gravity step. Time passed while paused is
+/// ignored.
+#[derive(Debug,Clone,Default)]
+pub struct GravityTimer{
+    elapsed:Duration,
+    paused:bool
+}
+
+impl GravityTimer {
+    pub fn advance(&mut self,elapsed:Duration) {
+        if !self.paused {
+            self.elapsed += elapsed;
+        }
+    }
+
+    /// Consumes one step's worth of time if enough has built up.
+    pub fn take_step(&mut self,interval:Duration) -> bool {
+        if self.elapsed < interval {
+            return false;
+        }
+        self.elapsed -= interval;
+        true
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
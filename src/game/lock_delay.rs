@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+/// Guideline extended placement: a grounded piece locks once `delay` has passed
+/// without it moving. Each successful move or rotation after the piece first
+/// touches down restarts the delay, up to `limit` times; reaching a new lowest
+/// row gives all of them back.
+#[derive(Debug,Clone)]
+pub struct LockDelay{
+    delay:Duration,
+    limit:u32,
+    elapsed:Duration,
+    resets:u32,
+    lowest:i32,
+    touched:bool
+}
+
+impl LockDelay {
+    pub fn new(delay:Duration,limit:u32) -> Self {
+        LockDelay{
+            delay,
+            limit,
+            elapsed:Duration::ZERO,
+            resets:0,
+            lowest:i32::MIN,
+            touched:false
+        }
+    }
+
+    /// Starts over for a freshly spawned piece whose lowest block is on row `y`.
+    pub fn reset(&mut self,y:i32) {
+        self.elapsed = Duration::ZERO;
+        self.resets = 0;
+        self.lowest = y;
+        self.touched = false;
+    }
+
+    /// Notes that the piece's lowest block is now on row `y`.
+    pub fn fell_to(&mut self,y:i32) {
+        if y > self.lowest {
+            self.reset(y);
+        }
+    }
+
+    pub fn moved(&mut self) {
+        if self.touched && self.resets < self.limit {
+            self.resets += 1;
+            self.elapsed = Duration::ZERO;
+        }
+    }
+
+    /// Advances the delay while the piece is `grounded` and reports whether it
+    /// should lock now.
+    pub fn advance(&mut self,elapsed:Duration,grounded:bool) -> bool {
+        if !grounded {
+            self.elapsed = Duration::ZERO;
+            return false;
+        }
+        self.touched = true;
+        self.elapsed += elapsed;
+        self.elapsed >= self.delay || self.resets >= self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY:Duration = Duration::from_millis(500);
+    const STEP:Duration = Duration::from_millis(100);
+
+    fn grounded() -> LockDelay {
+        let mut lock_delay = LockDelay::new(DELAY, 3);
+        lock_delay.reset(5);
+        assert!(!lock_delay.advance(Duration::ZERO, true));
+        lock_delay
+    }
+
+    #[test]
+    fn locks_after_the_delay_while_grounded() {
+        let mut lock_delay = grounded();
+        for _ in 0 .. 4 {
+            assert!(!lock_delay.advance(STEP, true));
+        }
+        assert!(lock_delay.advance(STEP, true));
+    }
+
+    #[test]
+    fn leaving_the_ground_restarts_the_delay() {
+        let mut lock_delay = grounded();
+        lock_delay.advance(STEP * 3, true);
+        assert!(!lock_delay.advance(STEP, false));
+        assert!(!lock_delay.advance(STEP * 4, true));
+        assert!(lock_delay.advance(STEP, true));
+    }
+
+    #[test]
+    fn each_move_resets_up_to_the_limit() {
+        let mut lock_delay = grounded();
+        for _ in 0 .. 2 {
+            assert!(!lock_delay.advance(STEP * 4, true));
+            lock_delay.moved();
+        }
+        assert!(!lock_delay.advance(STEP * 4, true));
+    }
+
+    #[test]
+    fn locks_at_once_when_the_limit_is_hit() {
+        let mut lock_delay = grounded();
+        for _ in 0 .. 3 {
+            lock_delay.moved();
+        }
+        assert!(lock_delay.advance(Duration::ZERO, true));
+    }
+
+    #[test]
+    fn new_lowest_row_restores_every_reset() {
+        let mut lock_delay = grounded();
+        for _ in 0 .. 3 {
+            lock_delay.moved();
+        }
+        // 回到同一行或更高的行不算
+        lock_delay.fell_to(5);
+        lock_delay.fell_to(4);
+        assert!(lock_delay.advance(Duration::ZERO, true));
+        lock_delay.fell_to(6);
+        assert!(!lock_delay.advance(STEP, true));
+        for _ in 0 .. 2 {
+            lock_delay.moved();
+        }
+        assert!(!lock_delay.advance(STEP, true));
+    }
+
+    #[test]
+    fn moves_before_touching_down_are_free() {
+        let mut lock_delay = LockDelay::new(DELAY, 3);
+        lock_delay.reset(5);
+        for _ in 0 .. 10 {
+            lock_delay.moved();
+            assert!(!lock_delay.advance(STEP, false));
+        }
+        assert!(!lock_delay.advance(STEP, true));
+        lock_delay.moved();
+        lock_delay.moved();
+        assert!(!lock_delay.advance(STEP, true));
+    }
+}
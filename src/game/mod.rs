@@ -0,0 +1,514 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use crate::constant::constant;
+use crate::model::{Canvas, CanvasPiece, Direction, PieceGenerator, PieceType};
+
+pub use config::GameConfig;
+pub use gravity::{GravityCurve, GravityTimer};
+pub use lock_delay::LockDelay;
+pub use mode::GameMode;
+pub use scoring::{Scoring, Statistics, TSpin};
+pub use session::{Clock, GameSession};
+
+mod config;
+mod gravity;
+mod lock_delay;
+mod mode;
+mod scoring;
+mod session;
+
+/// A player action fed into [`Game::handle`].
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+pub enum Input{
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateClockwise,
+    RotateCounterClockwise,
+    Rotate180,
+    Hold,
+    /// Pauses a running game or resumes a paused one.
+    Pause
+}
+
+/// Something that happened while handling an input or a tick.
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+pub enum GameEvent{
+    Moved,
+    Rotated,
+    Held,
+    Dropped,
+    HardDropped(u32),
+    Locked,
+    TSpin(TSpin),
+    LinesCleared(usize),
+    Scored(u64),
+    LevelUp(u32),
+    Paused,
+    Resumed,
+    Spawned,
+    /// The mode's goal was reached. The game is over from here on.
+    Completed,
+    GameOver
+}
+
+/// The rules of the game, independent of any front end.
+///
+/// [`Game::canvas`] only holds the locked stack. The active piece stays out of
+/// it until it locks, so front ends draw [`Game::piece`] over the canvas.
+#[derive(Debug,Clone)]
+pub struct Game{
+    canvas:Canvas,
+    piece:CanvasPiece,
+    generator:Box<dyn PieceGenerator>,
+    queue:VecDeque<PieceType>,
+    hold:Option<PieceType>,
+    /// Cleared once a piece has been held, set again when the next piece locks.
+    can_hold:bool,
+    /// The kick test used by the last successful move, if that move was a
+    /// rotation, and whether that rotation was a half turn.
+    last_rotation:Option<(usize,bool)>,
+    scoring:Scoring,
+    gravity:GravityCurve,
+    timer:GravityTimer,
+    lock_delay:LockDelay,
+    mode:GameMode,
+    /// Whether the game runs on the default board with the default randomizer.
+    standard:bool,
+    /// Play time so far, not counting pauses.
+    time:Duration,
+    completed:bool,
+    over:bool
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Game::with_config(GameConfig::default())
+    }
+
+    pub fn with_config(config:GameConfig) -> Self {
+        let generator = config.randomizer.generator(config.seed);
+        let standard = config.standard_board();
+        Game{standard, ..Game::with_generator(config, generator)}
+    }
+
+    /// Like [`Game::with_config`], but deals from `generator` instead of the
+    /// configured randomizer. Such a game never counts as a standard one.
+    pub fn with_generator(config:GameConfig,mut generator:Box<dyn PieceGenerator>) -> Self {
+        let canvas = Canvas::new(
+            config.width.clamp(constant::MIN_BOARD_WIDTH, constant::MAX_BOARD_WIDTH),
+            config.height.clamp(constant::MIN_BOARD_HEIGHT, constant::MAX_BOARD_HEIGHT),
+            config.hidden.clamp(constant::MIN_HIDDEN_ROWS, constant::MAX_HIDDEN_ROWS)
+        );
+        let preview = config.preview.clamp(constant::MIN_PREVIEW, constant::MAX_PREVIEW);
+        let piece = CanvasPiece::next(generator.as_mut(), &canvas);
+        let queue = (0 .. preview).map(|_| generator.next()).collect();
+        let scoring = Scoring::new(config.start_level);
+        Game{
+            canvas,
+            piece,
+            generator,
+            queue,
+            hold:None,
+            can_hold:true,
+            last_rotation:None,
+            scoring,
+            gravity:config.gravity,
+            timer:GravityTimer::default(),
+            lock_delay:LockDelay::new(config.lock_delay, config.move_reset_limit),
+            mode:config.mode,
+            standard:false,
+            time:Duration::ZERO,
+            completed:false,
+            over:false
+        }
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    pub fn piece(&self) -> &CanvasPiece {
+        &self.piece
+    }
+
+    /// The upcoming pieces, next one first.
+    pub fn preview(&self) -> &VecDeque<PieceType> {
+        &self.queue
+    }
+
+    pub fn hold(&self) -> Option<PieceType> {
+        self.hold
+    }
+
+    pub fn scoring(&self) -> &Scoring {
+        &self.scoring
+    }
+
+    /// The cells where the active piece would land, for drawing its ghost.
+    pub fn ghost(&self) -> Vec<(i32,i32)> {
+        let distance = self.piece.drop_distance(&self.canvas);
+        self.piece.blocks.iter().map(|p| (p.x, p.y + distance)).collect()
+    }
+
+    /// The current time per gravity step, zero at 20G.
+    pub fn gravity_interval(&self) -> Duration {
+        let level = if self.mode.fixed_gravity() { self.scoring.start_level() } else { self.scoring.level() };
+        self.gravity.interval(level)
+    }
+
+    /// Freezes the gravity and lock clocks and ignores every input but
+    /// [`Input::Pause`] until [`Game::resume`] is called.
+    pub fn pause(&mut self) {
+        self.timer.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.timer.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.timer.is_paused()
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// Whether the game was started with the default board size, preview and
+    /// randomizer, so its result can go in the high score tables.
+    pub fn is_standard(&self) -> bool {
+        self.standard
+    }
+
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Locked pieces per second of play time.
+    pub fn pieces_per_second(&self) -> f64 {
+        let seconds = self.time.as_secs_f64();
+        if seconds > 0.0 {
+            self.scoring.statistics().pieces as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    /// Whether the game ended by reaching the mode's goal rather than topping out.
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    pub fn handle(&mut self,input:Input) -> Vec<GameEvent> {
+        if self.over {
+            return Vec::new();
+        }
+        if self.is_paused() && input != Input::Pause {
+            return Vec::new();
+        }
+        match input {
+            Input::MoveLeft => self.horizontal_move(true),
+            Input::MoveRight => self.horizontal_move(false),
+            Input::RotateClockwise => self.rotate(self.piece.direction.clockwise()),
+            Input::RotateCounterClockwise => self.rotate(self.piece.direction.counter_clockwise()),
+            Input::Rotate180 => self.rotate(self.piece.direction.opposite()),
+            Input::SoftDrop => {
+                if !self.step_down() {
+                    return Vec::new();
+                }
+                self.scoring.soft_drop(1);
+                self.timer.reset();
+                vec![GameEvent::Dropped]
+            }
+            Input::HardDrop => {
+                let rows = self.piece.hard_drop(&self.canvas) as u32;
+                if rows > 0 {
+                    self.last_rotation = None;
+                }
+                self.scoring.hard_drop(rows);
+                let mut events = vec![GameEvent::HardDropped(rows)];
+                events.extend(self.lock());
+                events
+            }
+            Input::Hold => self.swap_hold(),
+            Input::Pause => self.toggle_pause()
+        }
+    }
+
+    /// Advances the gravity and lock clocks by `elapsed`. The piece moves down
+    /// for every gravity step that became due, straight to the floor at 20G, and
+    /// locks once it has rested on the stack for the lock delay.
+    pub fn tick(&mut self,elapsed:Duration) -> Vec<GameEvent> {
+        if self.over || self.timer.is_paused() {
+            return Vec::new();
+        }
+        self.time += elapsed;
+        if let Some(limit) = self.mode.time_limit() {
+            self.time = self.time.min(limit);
+        }
+        if self.mode.completed(self.scoring.lines(), self.time) {
+            return self.complete(Vec::new());
+        }
+        self.timer.advance(elapsed);
+        let interval = self.gravity_interval();
+        let mut dropped = false;
+        if interval.is_zero() {
+            self.timer.reset();
+            while self.step_down() {
+                dropped = true;
+            }
+        } else {
+            while self.timer.take_step(interval) {
+                if !self.step_down() {
+                    self.timer.reset();
+                    break;
+                }
+                dropped = true;
+            }
+        }
+        let mut events = Vec::new();
+        if dropped {
+            events.push(GameEvent::Dropped);
+        }
+        let grounded = !self.piece.can_drop_down(&self.canvas);
+        if self.lock_delay.advance(elapsed, grounded) {
+            events.extend(self.lock());
+        }
+        events
+    }
+
+    fn complete(&mut self,mut events:Vec<GameEvent>) -> Vec<GameEvent> {
+        self.over = true;
+        self.completed = true;
+        events.push(GameEvent::Completed);
+        events
+    }
+
+    fn toggle_pause(&mut self) -> Vec<GameEvent> {
+        if self.is_paused() {
+            self.resume();
+            vec![GameEvent::Resumed]
+        } else {
+            self.pause();
+            vec![GameEvent::Paused]
+        }
+    }
+
+    fn horizontal_move(&mut self,move_left:bool) -> Vec<GameEvent> {
+        if self.piece.horizontal_move(&self.canvas, move_left) {
+            self.last_rotation = None;
+            self.lock_delay.moved();
+            vec![GameEvent::Moved]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn rotate(&mut self,direction:Direction) -> Vec<GameEvent> {
+        let half_turn = direction == self.piece.direction.opposite();
+        if let Some(kick) = self.piece.rotate(&self.canvas, direction) {
+            self.last_rotation = Some((kick, half_turn));
+            self.lock_delay.moved();
+            self.lock_delay.fell_to(self.piece.lowest_row());
+            vec![GameEvent::Rotated]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Puts the active piece in the hold slot and brings out the previously held
+    /// piece, or the next one from the queue when the slot was empty.
+    fn swap_hold(&mut self) -> Vec<GameEvent> {
+        if !self.can_hold {
+            return Vec::new();
+        }
+        let spawned = match self.hold.replace(self.piece.r#type) {
+            Some(piece_type) => self.spawn(piece_type),
+            None => self.spawn_next()
+        };
+        self.can_hold = false;
+        if !spawned {
+            return vec![GameEvent::Held, GameEvent::GameOver];
+        }
+        vec![GameEvent::Held]
+    }
+
+    /// Three-corner rule: a T piece that last moved by rotating and has three of
+    /// its four corners filled is a T-spin. It is only a mini when one of the two
+    /// corners it points at is open, unless it got there with the last kick test
+    /// of a quarter turn.
+    fn t_spin(&self) -> TSpin {
+        let Some((kick, half_turn)) = self.last_rotation else {
+            return TSpin::None;
+        };
+        if self.piece.r#type != PieceType::T {
+            return TSpin::None;
+        }
+        let (front, back) = self.piece.t_corners(&self.canvas);
+        // SRS 的第五个测试是 TST 踢墙，180 度的表第五项只是平移
+        let tst_kick = kick == 4 && !half_turn;
+        if front + back < 3 {
+            TSpin::None
+        } else if front == 2 || tst_kick {
+            TSpin::Full
+        } else {
+            TSpin::Mini
+        }
+    }
+
+    fn spawn_next(&mut self) -> bool {
+        let piece_type = self.queue.pop_front().unwrap();
+        self.queue.push_back(self.generator.next());
+        self.spawn(piece_type)
+    }
+
+    /// Brings `piece_type` into play. Returns `false` and ends the game when it
+    /// overlaps the stack where it spawns (block out).
+    fn spawn(&mut self,piece_type:PieceType) -> bool {
+        self.piece = CanvasPiece::spawn(piece_type, &self.canvas);
+        self.last_rotation = None;
+        self.timer.reset();
+        self.lock_delay.reset(self.piece.lowest_row());
+        if self.piece.blocked(&self.canvas) {
+            self.over = true;
+            return false;
+        }
+        true
+    }
+
+    /// Moves the piece one row down, returning `false` when it is resting on the stack.
+    fn step_down(&mut self) -> bool {
+        if !self.piece.drop_down(&self.canvas) {
+            return false;
+        }
+        self.last_rotation = None;
+        self.lock_delay.fell_to(self.piece.lowest_row());
+        true
+    }
+
+    fn lock(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![GameEvent::Locked];
+        self.piece.stamp(&mut self.canvas);
+        // 整个方块都锁在隐藏行里（lock out）
+        if self.piece.above_visible(&self.canvas) {
+            self.over = true;
+            events.push(GameEvent::GameOver);
+            return events;
+        }
+        let t_spin = self.t_spin();
+        if t_spin != TSpin::None {
+            events.push(GameEvent::TSpin(t_spin));
+        }
+        let cleared = self.piece.success(&mut self.canvas);
+        if cleared > 0 {
+            events.push(GameEvent::LinesCleared(cleared));
+        }
+        let level = self.scoring.level();
+        let points = self.scoring.lock(cleared, t_spin);
+        if points > 0 {
+            events.push(GameEvent::Scored(points));
+        }
+        if self.scoring.level() > level {
+            events.push(GameEvent::LevelUp(self.scoring.level()));
+        }
+        if self.mode.completed(self.scoring.lines(), self.time) {
+            return self.complete(events);
+        }
+        self.can_hold = true;
+        if self.spawn_next() {
+            events.push(GameEvent::Spawned);
+        } else {
+            events.push(GameEvent::GameOver);
+        }
+        events
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Cell;
+
+    /// Deals `pieces` in order, then keeps dealing the last one.
+    #[derive(Debug,Clone)]
+    struct Sequence{
+        pieces:Vec<PieceType>,
+        next:usize
+    }
+
+    impl PieceGenerator for Sequence {
+        fn next(&mut self) -> PieceType {
+            let piece_type = self.pieces[self.next.min(self.pieces.len() - 1)];
+            self.next += 1;
+            piece_type
+        }
+
+        fn clone_box(&self) -> Box<dyn PieceGenerator> {
+            Box::new(self.clone())
+        }
+    }
+
+    /// A four column board with `height` visible rows and two hidden ones.
+    fn game(height:usize,pieces:&[PieceType]) -> Game {
+        let config = GameConfig{width:4, height, hidden:2, preview:1, ..GameConfig::default()};
+        Game::with_generator(config, Box::new(Sequence{pieces:pieces.to_vec(), next:0}))
+    }
+
+    /// Stands the I piece up against the left wall and drops it.
+    fn drop_vertical_i(game:&mut Game) -> Vec<GameEvent> {
+        assert_eq!(game.piece().r#type, PieceType::I);
+        assert_eq!(game.handle(Input::RotateClockwise), vec![GameEvent::Rotated]);
+        while !game.handle(Input::MoveLeft).is_empty() {}
+        game.handle(Input::HardDrop)
+    }
+
+    #[test]
+    fn spawning_into_the_stack_blocks_out() {
+        // 第二根竖 I 伸进隐藏行，挡住下一根 I 出生的位置
+        let mut game = game(6, &[PieceType::I]);
+        assert!(drop_vertical_i(&mut game).contains(&GameEvent::Spawned));
+        let events = drop_vertical_i(&mut game);
+        assert_eq!(events.last(), Some(&GameEvent::GameOver));
+        assert!(!events.contains(&GameEvent::Spawned));
+        assert!(game.is_over());
+        assert!(!game.is_completed());
+        assert_eq!(game.handle(Input::MoveRight), vec![]);
+    }
+
+    #[test]
+    fn locking_inside_the_hidden_rows_locks_out() {
+        let mut game = game(4, &[PieceType::O]);
+        for _ in 0 .. 2 {
+            assert_eq!(game.handle(Input::HardDrop).last(), Some(&GameEvent::Spawned));
+        }
+        let events = game.handle(Input::HardDrop);
+        assert_eq!(events, vec![GameEvent::HardDropped(0), GameEvent::Locked, GameEvent::GameOver]);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn locking_partly_above_the_field_carries_on() {
+        let mut game = game(6, &[PieceType::I, PieceType::I, PieceType::O]);
+        drop_vertical_i(&mut game);
+        let events = drop_vertical_i(&mut game);
+        assert_eq!(events.last(), Some(&GameEvent::Spawned));
+        assert!(!game.is_over());
+        // 露在隐藏行里的两格还留在画布上
+        for y in 0 .. 4 {
+            assert_eq!(game.canvas().get(1, y), Cell::Locked(PieceType::I), "row {}", y);
+        }
+        assert_eq!(game.piece().r#type, PieceType::O);
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+use std::time::Duration;
+
+/// The rule set deciding when a game is won.
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+pub enum GameMode{
+    /// Play from the starting level with gravity speeding up every level, until
+    /// `goal` lines are cleared or, without a goal, until the stack tops out.
+    Marathon{goal:Option<u32>},
+    /// Clear `lines` lines as fast as possible.
+    Sprint{lines:u32},
+    /// Score as much as possible before `limit` runs out, with gravity held at
+    /// the starting level.
+    Ultra{limit:Duration}
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::marathon()
+    }
+}
+
+impl GameMode {
+    pub fn marathon() -> Self {
+        GameMode::Marathon{goal:Some(150)}
+    }
+
+    pub fn sprint() -> Self {
+        GameMode::Sprint{lines:40}
+    }
+
+    pub fn ultra(minutes:u64) -> Self {
+        GameMode::Ultra{limit:Duration::from_secs(minutes * 60)}
+    }
+
+    /// Whether the mode shows a running timer, which has to be redrawn every frame.
+    pub fn timed(&self) -> bool {
+        matches!(self, GameMode::Sprint{..} | GameMode::Ultra{..})
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        match self {
+            GameMode::Ultra{limit} => Some(*limit),
+            _ => None
+        }
+    }
+
+    /// Whether gravity ignores level ups and stays at the starting level.
+    pub fn fixed_gravity(&self) -> bool {
+        matches!(self, GameMode::Ultra{..})
+    }
+
+    /// Whether a game that has cleared `lines` lines in `time` has reached the goal.
+    pub fn completed(&self,lines:u32,time:Duration) -> bool {
+        match self {
+            GameMode::Marathon{goal} => goal.is_some_and(|goal| lines >= goal),
+            GameMode::Sprint{lines:goal} => lines >= *goal,
+            GameMode::Ultra{limit} => time >= *limit
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameMode::Marathon{goal:Some(goal)} => write!(f, "Marathon {} lines", goal),
+            GameMode::Marathon{goal:None} => write!(f, "Marathon endless"),
+            GameMode::Sprint{lines} => write!(f, "Sprint {} lines", lines),
+            GameMode::Ultra{limit} => write!(f, "Ultra {} min", limit.as_secs() / 60)
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::constant::constant;
+
+/// How a T piece was locked, judged by the three-corner rule.
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Default)]
+pub enum TSpin{
+    #[default]
+    None,
+    Mini,
+    Full
+}
+
+/// Running totals of the kinds of clears made during a game.
+#[derive(Debug,Clone,Default)]
+pub struct Statistics{
+    pub pieces:u32,
+    pub singles:u32,
+    pub doubles:u32,
+    pub triples:u32,
+    pub tetrises:u32,
+    pub t_spins:u32,
+    pub back_to_backs:u32,
+    pub max_combo:u32
+}
+
+/// Guideline scoring: line clears and T-spins scaled by level, back-to-back
+/// bonuses, combos and drop points, with a level up every `LINES_PER_LEVEL` lines.
+#[derive(Debug,Clone)]
+pub struct Scoring{
+    score:u64,
+    start_level:u32,
+    level:u32,
+    lines:u32,
+    /// Number of consecutive clearing locks minus one, `-1` when no combo is running.
+    combo:i32,
+    back_to_back:bool,
+    statistics:Statistics
+}
+
+impl Scoring {
+    pub fn new(start_level:u32) -> Self {
+        let start_level = start_level.max(1);
+        Scoring{
+            score:0,
+            start_level,
+            level:start_level,
+            lines:0,
+            combo:-1,
+            back_to_back:false,
+            statistics:Statistics::default()
+        }
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    pub fn start_level(&self) -> u32 {
+        self.start_level
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    pub fn combo(&self) -> i32 {
+        self.combo
+    }
+
+    pub fn back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    pub fn soft_drop(&mut self,cells:u32) {
+        self.score += cells as u64;
+    }
+
+    pub fn hard_drop(&mut self,cells:u32) {
+        self.score += 2 * cells as u64;
+    }
+
+    /// Scores a locked piece that cleared `lines` rows and returns the points awarded.
+    pub fn lock(&mut self,lines:usize,t_spin:TSpin) -> u64 {
+        self.statistics.pieces += 1;
+        let level = self.level as u64;
+        let mut points = match (t_spin, lines) {
+            (TSpin::None, 0) => 0,
+            (TSpin::None, 1) => 100,
+            (TSpin::None, 2) => 300,
+            (TSpin::None, 3) => 500,
+            (TSpin::None, _) => 800,
+            (TSpin::Mini, 0) => 100,
+            (TSpin::Mini, 1) => 200,
+            (TSpin::Mini, _) => 400,
+            (TSpin::Full, 0) => 400,
+            (TSpin::Full, 1) => 800,
+            (TSpin::Full, 2) => 1200,
+            (TSpin::Full, _) => 1600,
+        } * level;
+        if t_spin != TSpin::None {
+            self.statistics.t_spins += 1;
+        }
+        if lines == 0 {
+            self.combo = -1;
+            self.score += points;
+            return points;
+        }
+
+        let difficult = lines >= 4 || t_spin != TSpin::None;
+        if difficult && self.back_to_back {
+            points = points * 3 / 2;
+            self.statistics.back_to_backs += 1;
+        }
+        self.back_to_back = difficult;
+        self.combo += 1;
+        if self.combo > 0 {
+            points += 50 * self.combo as u64 * level;
+            self.statistics.max_combo = self.statistics.max_combo.max(self.combo as u32);
+        }
+        match lines {
+            1 => self.statistics.singles += 1,
+            2 => self.statistics.doubles += 1,
+            3 => self.statistics.triples += 1,
+            _ => self.statistics.tetrises += 1,
+        }
+
+        self.score += points;
+        self.lines += lines as u32;
+        self.level = self.level.max(self.start_level + self.lines / constant::LINES_PER_LEVEL);
+        points
+    }
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Scoring::new(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_clears_scale_with_level() {
+        let mut scoring = Scoring::new(3);
+        assert_eq!(scoring.lock(1, TSpin::None), 300);
+        assert_eq!(scoring.lock(0, TSpin::None), 0);
+        assert_eq!(scoring.lock(2, TSpin::None), 900);
+        assert_eq!(scoring.lock(0, TSpin::None), 0);
+        assert_eq!(scoring.lock(3, TSpin::None), 1500);
+        assert_eq!(scoring.lock(0, TSpin::None), 0);
+        assert_eq!(scoring.lock(4, TSpin::None), 2400);
+        assert_eq!(scoring.score(), 300 + 900 + 1500 + 2400);
+        assert_eq!(scoring.lines(), 10);
+    }
+
+    #[test]
+    fn t_spin_table() {
+        let cases = [
+            (TSpin::Mini, 0, 100),
+            (TSpin::Mini, 1, 200),
+            (TSpin::Mini, 2, 400),
+            (TSpin::Full, 0, 400),
+            (TSpin::Full, 1, 800),
+            (TSpin::Full, 2, 1200),
+            (TSpin::Full, 3, 1600),
+        ];
+        for (t_spin, lines, points) in cases {
+            assert_eq!(Scoring::new(1).lock(lines, t_spin), points, "{:?} {}", t_spin, lines);
+        }
+        let mut scoring = Scoring::new(1);
+        scoring.lock(0, TSpin::Full);
+        assert_eq!(scoring.statistics().t_spins, 1);
+    }
+
+    #[test]
+    fn back_to_back_only_on_difficult_clears() {
+        let mut scoring = Scoring::new(1);
+        assert_eq!(scoring.lock(4, TSpin::None), 800);
+        assert!(scoring.back_to_back());
+        // 不消行的 T-spin 不打断也不享受 B2B
+        assert_eq!(scoring.lock(0, TSpin::Full), 400);
+        assert!(scoring.back_to_back());
+        assert_eq!(scoring.lock(2, TSpin::Full), 1800);
+        assert_eq!(scoring.statistics().back_to_backs, 1);
+        scoring.lock(0, TSpin::None);
+        assert_eq!(scoring.lock(1, TSpin::None), 100);
+        assert!(!scoring.back_to_back());
+        scoring.lock(0, TSpin::None);
+        assert_eq!(scoring.lock(4, TSpin::None), 800);
+    }
+
+    #[test]
+    fn combo_resets_on_a_lock_without_clears() {
+        let mut scoring = Scoring::new(2);
+        assert_eq!(scoring.lock(1, TSpin::None), 200);
+        assert_eq!(scoring.combo(), 0);
+        assert_eq!(scoring.lock(1, TSpin::None), 200 + 100);
+        assert_eq!(scoring.lock(1, TSpin::None), 200 + 200);
+        assert_eq!(scoring.statistics().max_combo, 2);
+        scoring.lock(0, TSpin::None);
+        assert_eq!(scoring.combo(), -1);
+        assert_eq!(scoring.lock(1, TSpin::None), 200);
+        assert_eq!(scoring.statistics().max_combo, 2);
+    }
+
+    #[test]
+    fn level_counts_from_the_start_level() {
+        let mut scoring = Scoring::new(5);
+        for _ in 0 .. constant::LINES_PER_LEVEL - 1 {
+            scoring.lock(1, TSpin::None);
+            scoring.lock(0, TSpin::None);
+        }
+        assert_eq!(scoring.level(), 5);
+        scoring.lock(1, TSpin::None);
+        assert_eq!(scoring.level(), 6);
+        assert_eq!(scoring.start_level(), 5);
+        assert_eq!(Scoring::new(0).level(), 1);
+    }
+
+    #[test]
+    fn drops_score_per_cell() {
+        let mut scoring = Scoring::default();
+        scoring.soft_drop(3);
+        scoring.hard_drop(5);
+        assert_eq!(scoring.score(), 13);
+    }
+}
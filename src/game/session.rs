@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use crate::game::{Game, GameEvent, Input};
+
+/// A shared, thread-safe handle to one running [`Game`].
+///
+/// Every clone refers to the same game. Inputs and gravity ticks coming from
+/// different threads are serialized through a single lock, so a tick can never
+/// land halfway through a move. Front ends that render should hold the guard
+/// returned by [`GameSession::lock`] until the frame is drawn.
+#[derive(Debug,Clone,Default)]
+pub struct GameSession{
+    game:Arc<Mutex<Game>>
+}
+
+impl GameSession {
+    pub fn new(game:Game) -> Self {
+        GameSession{
+            game:Arc::new(Mutex::new(game))
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Game> {
+        self.game.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn handle(&self,input:Input) -> Vec<GameEvent> {
+        self.lock().handle(input)
+    }
+
+    pub fn tick(&self,elapsed:Duration) -> Vec<GameEvent> {
+        self.lock().tick(elapsed)
+    }
+
+    /// Starts a thread that ticks the game every `frame` with the real time that
+    /// passed, calling `on_tick` with the lock still held so it can render.
+    /// The thread runs until the returned [`Clock`] is stopped or dropped.
+    pub fn start_clock<F>(&self,frame:Duration,mut on_tick:F) -> Clock
+        where F: FnMut(&Game,&[GameEvent]) + Send + 'static {
+        let session = self.clone();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let flag = stopped.clone();
+        let handle = thread::spawn(move || {
+            let mut last = Instant::now();
+            while !flag.load(Ordering::Relaxed) {
+                thread::sleep(frame);
+                let now = Instant::now();
+                let mut game = session.lock();
+                let events = game.tick(now - last);
+                on_tick(&game, &events);
+                last = now;
+            }
+        });
+        Clock{
+            stopped,
+            handle:Some(handle)
+        }
+    }
+}
+
+/// The gravity thread started by [`GameSession::start_clock`].
+#[derive(Debug)]
+pub struct Clock{
+    stopped:Arc<AtomicBool>,
+    handle:Option<JoinHandle<()>>
+}
+
+impl Clock {
+    pub fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Clock {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
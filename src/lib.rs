@@ -0,0 +1,10 @@
+//! Headless tetris engine.
+//!
+//! The [`game::Game`] state machine owns the board and the falling piece, takes
+//! inputs and ticks, and reports what happened as [`game::GameEvent`]s. It never
+//! touches the terminal, so the rules can be driven by bots, tests or any front end.
+
+pub mod constant;
+pub mod enumerate;
+pub mod game;
+pub mod model;
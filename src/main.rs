@@ -1,207 +1,296 @@
-use std::io::{stdout, Write};
-use std::ops::Deref;
+use std::env;
+use std::io::stdout;
+use std::process::exit;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-use crossterm::cursor::MoveTo;
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers, read};
+use std::time::{Duration, Instant};
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags, poll, read};
 use crossterm::execute;
-use crossterm::style::{ Stylize};
-use crossterm::terminal::{Clear, ClearType, disable_raw_mode, enable_raw_mode};
-use lazy_static::lazy_static;
-use crate::common::transform_symbol;
-use crate::enumerate::Color;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement};
+use tetris::constant::constant;
+use tetris::game::{Clock, Game, GameConfig, GameEvent, GameMode, GameSession, Input};
 
-mod constant;
-mod enumerate;
-mod model;
-mod common;
+use crate::terminal::{clear, cli, render, scores, settings, show_game_over};
+use crate::terminal::auto_repeat::AutoRepeat;
+use crate::terminal::controls::{Action, Controls};
+use crate::terminal::frame::Renderer;
+use crate::terminal::menu::{main_menu, prompt, select, MenuItem};
+use crate::terminal::scores::HighScores;
+use crate::terminal::theme::Theme;
 
+mod terminal;
 
-use crate::model::{ShowSelf, Canvas, CanvasPixel, CanvasPiece, PieceType, Direction};
 
+/// The player's name, results and controls, kept across games.
+struct Profile{
+    name:String,
+    scores:HighScores,
+    controls:Controls,
+    /// Whether the terminal reports key releases.
+    releases:bool,
+    theme:Theme
+}
 
-const HELP: &str = r#"Press Ctrl+Q to end, press c then u to start the game：
-"#;
-
-static mut CURR_PIECE:CanvasPiece = CanvasPiece{
-    r#type:PieceType::I,
-    direction:Direction::Up,
-    x:constant::constant::INIT_COORDINATE_X,
-    y:constant::constant::INIT_COORDINATE_Y,
-    canvas:None,
-    pixels:Vec::new()
-};
+/// What the player picked on the game-over screen, or how they left the game.
+#[derive(Eq,PartialEq)]
+enum AfterGame{
+    Restart,
+    Menu,
+    Quit
+}
 
-lazy_static!{
-    static ref CURR_PIECE_LOCK:Arc<Mutex<usize>> = Arc::new(Mutex::new(0));
+/// How a game is put on screen, shared between the input loop and the clock.
+#[derive(Clone)]
+struct View{
+    theme:Theme,
+    renderer:Arc<Mutex<Renderer>>
 }
 
-fn print_events() -> crossterm<()> {
-    let mut canvas = Canvas{
-        pixels:Vec::with_capacity(22),
-    };
-    for i in 0 .. canvas.pixels.capacity() {
-        let mut y = Vec::with_capacity(12) as Vec<CanvasPixel>;
-        for j in 0 .. y.capacity() {
-            if i == canvas.pixels.capacity() - 1 {
-                y.push(CanvasPixel{
-                    color:Color::Red,
-                    symbol:transform_symbol("□"),
-                    x:j as i32,
-                    y:i as i32
-                });
-            } else if j == 0 || j == y.capacity() - 1 {
-                y.push(CanvasPixel{
-                    color:Color::Red,
-                    symbol:transform_symbol("□"),
-                    x:j as i32,
-                    y:i as i32
-                })
-            } else {
-                y.push(CanvasPixel{
-                    color:Color::Green,
-                    symbol:transform_symbol(" "),
-                    x:j as i32,
-                    y:i as i32
-                })
-            }
-        }
-        canvas.pixels.push(y);
+impl View {
+    fn new(theme:&Theme) -> Self {
+        View{theme:theme.clone(), renderer:Arc::new(Mutex::new(Renderer::default()))}
     }
-    unsafe {
-        CURR_PIECE = CanvasPiece::next(Some(canvas));
+
+    /// Draws `game`. Callers hold the session lock, so frames go out in order.
+    fn show(&self,game:&Game) {
+        let mut renderer = self.renderer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = render(game, &self.theme, &mut renderer);
     }
-    let canvas:&Canvas;
-    unsafe {
-        canvas = CURR_PIECE.as_ref().unwrap();
+}
+
+fn show_events(game:&Game,events:&[GameEvent],view:&View) {
+    // 计时的模式就算什么都没发生也要刷新时间
+    let changed = !events.is_empty() || game.mode().timed() && !game.is_paused();
+    // 结束的那一帧也画出来，Ultra 的倒计时才会停在 0:00.000
+    if changed {
+        view.show(game);
     }
-    let mut game_started = false;
-    let lock_done = CURR_PIECE_LOCK.clone();
-    loop {
-        let event = read()?;
-        if event == Event::Key(KeyCode::Char('c').into()) {
-            clear();
-            let _ = goto(0,0);
-        }
+}
 
-        if event == Event::Key(KeyCode::Char('p').into()) {
-            println!("{}", "□〠".len());
-        }
+fn handle_inputs(session:&GameSession,inputs:&[Input],view:&View) {
+    if inputs.is_empty() {
+        return;
+    }
+    let mut game = session.lock();
+    let events:Vec<GameEvent> = inputs.iter().flat_map(|input| game.handle(*input)).collect();
+    show_events(&game, &events, view);
+}
 
-        if event == Event::Key(KeyCode::Char('f').into()) {
-            println!("{}", concat!("red foreground color\r", "***concat***").red());
-            println!("{}", "white foreground color\r".white());
-            println!("{}", "green background color\r".on_green());
-            println!("{}", "white background color\r".on_white());
-        }
+fn new_session(config:&GameConfig) -> GameSession {
+    GameSession::new(Game::with_config(GameConfig{
+        seed:rand::random(),
+        ..config.clone()
+    }))
+}
 
-        if event == Event::Key(KeyCode::Char('m').into()) {
-            let _ = goto(0,0);
-        }
+fn start_clock(session:&GameSession,view:&View) -> Clock {
+    view.show(&session.lock());
+    let view = view.clone();
+    session.start_clock(Duration::from_millis(constant::FRAME_MILLIS), move |game, events| {
+        show_events(game, events, &view);
+    })
+}
 
-        if event == Event::Key(KeyCode::Char('y').into()) {
-            println!("{}", "▉▉▉▉▓▓▓▓▓▓██▉▉☾☽██◀▶██████╲╱██◑◐██ღღ88");
-            println!("{}", "◇◆●○■□██◁▶▉▉██╳╳><╱╲██♪♩▓▉88");
-        }
+/// Asks for a name and saves the result if the game made its mode's high score
+/// table. Returns the place it took and a notice to show with it.
+fn record(game:&Game,profile:&mut Profile) -> std::io::Result<(Option<usize>,String)> {
+    if !profile.scores.qualifies(game) {
+        return Ok((None, String::new()));
+    }
+    clear();
+    let Some(name) = prompt("NEW HIGH SCORE - ENTER YOUR NAME", &profile.name, 11)? else {
+        return Ok((None, String::new()));
+    };
+    profile.name = name;
+    let rank = profile.scores.insert(game, profile.name.clone());
+    let notice = match profile.scores.save() {
+        Ok(()) => String::new(),
+        Err(e) => format!("Could not save high scores: {}", e)
+    };
+    Ok((rank, notice))
+}
 
-        if event == Event::Key(KeyCode::Char('z').into()) {
-            unsafe {
-                let string = format!("{}{}","\n",CURR_PIECE.y);
-                println!("{}",transform_symbol(string.deref()));
+fn game_over(session:&GameSession,profile:&mut Profile) -> std::io::Result<AfterGame> {
+    let game = session.lock();
+    let (rank, notice) = record(&game, profile)?;
+    show_game_over(&game, &profile.scores, rank, &notice);
+    drop(game);
+    loop {
+        if let Event::Key(key) = read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
             }
-            flush_output();
-        }
-
-        if event == Event::Key(KeyCode::Up.into()) {
-            unsafe {
-                CURR_PIECE.rotate();
+            match key.code {
+                KeyCode::Char('r') => return Ok(AfterGame::Restart),
+                KeyCode::Char('m') => return Ok(AfterGame::Menu),
+                KeyCode::Char('q') => return Ok(AfterGame::Quit),
+                _ => {}
             }
-            print!("{}",transform_symbol("\r\n"));
-            flush_output();
         }
+    }
+}
 
-        if event == Event::Key(KeyCode::Right.into()) {
-            unsafe {
-                CURR_PIECE.horizontal_move(false);
-            }
-            print!("{}", transform_symbol("\r→"));
-            flush_output();
-        }
+/// Leaves the final board up for a moment, dropping any keys pressed meanwhile
+/// so they don't pick an option on the results screen.
+fn hold_final_board() -> std::io::Result<()> {
+    thread::sleep(Duration::from_millis(constant::TIME_UP_MILLIS));
+    while poll(Duration::ZERO)? {
+        read()?;
+    }
+    Ok(())
+}
 
-        if event == Event::Key(KeyCode::Down.into()) {
-            unsafe {
-                CURR_PIECE.drop_down()
+/// Plays games with `config` until the player goes back to the menu or quits.
+fn play(config:&GameConfig,profile:&mut Profile) -> std::io::Result<AfterGame> {
+    loop {
+        let session = new_session(config);
+        execute!(stdout(),Hide)?;
+        // 每局换一个新的 Renderer，第一帧整屏重画
+        let view = View::new(&profile.theme);
+        let mut clock = start_clock(&session, &view);
+        let mut repeat = AutoRepeat::new(profile.controls.handling, profile.releases);
+        let mut last = Instant::now();
+        loop {
+            let (over, paused, gravity) = {
+                let game = session.lock();
+                (game.is_over(), game.is_paused(), game.gravity_interval())
+            };
+            if over {
+                clock.stop();
+                let time_up = {
+                    let game = session.lock();
+                    game.mode().time_limit() == Some(game.time())
+                };
+                if time_up {
+                    hold_final_board()?;
+                }
+                break;
             }
-            print!("{}", transform_symbol("\r↓"));
-            flush_output();
-        }
-
-        if event == Event::Key(KeyCode::Left.into()) {
-            unsafe {
-                // 不用unsafe会提示curr_piece可能被并发修改，但是我确实只用了一个线程因此unsafe【还有个问题，为什么用drop方法会提示必须用mut定义？】
-                CURR_PIECE.horizontal_move(true);
+            let now = Instant::now();
+            if paused {
+                repeat.reset();
+            } else {
+                handle_inputs(&session, &repeat.update(now - last, gravity), &view);
             }
-            print!("{}", transform_symbol("\r←"));
-            flush_output();
-        }
-        
-        if event == Event::Key(KeyCode::Char('u').into()) {
-            if game_started {
+            last = now;
+            if !poll(Duration::from_millis(constant::FRAME_MILLIS))? {
                 continue;
             }
-            game_started = false;
-            canvas.show_self();
-            let lock_clone = CURR_PIECE_LOCK.clone();
-            thread::spawn(move || {
-                loop {
-                    thread::sleep(Duration::from_millis(1500));
-                    unsafe {
-                        CURR_PIECE.drop_down();
-                    }
+            let event = read()?;
+            // 切到别的窗口时自动暂停
+            if event == Event::FocusLost && !paused {
+                handle_inputs(&session, &[Input::Pause], &view);
+                continue;
+            }
+            // 窗口大小变了，终端上的内容已经不可信，整屏重画
+            if let Event::Resize(_, _) = event {
+                let game = session.lock();
+                view.renderer.lock().unwrap_or_else(|e| e.into_inner()).invalidate();
+                view.show(&game);
+                continue;
+            }
+            let Event::Key(key) = event else {
+                continue;
+            };
+            let Some(action) = profile.controls.action(&key) else {
+                continue;
+            };
+            let Some(input) = action.input() else {
+                if action == Action::Quit && key.kind == KeyEventKind::Press {
+                    return Ok(AfterGame::Quit);
                 }
-            });
-        }
-        if event == Event::Key(KeyCode::Char('e').into()) {
-            let piece = CanvasPixel{
-                color:Color::Red,
-                symbol:"*".to_owned(),
-                x:5,
-                y:5
+                continue;
             };
-            piece.show_self();
-            flush_output();
+            match key.kind {
+                KeyEventKind::Release => repeat.release(input),
+                kind => handle_inputs(&session, &repeat.press(input, kind == KeyEventKind::Repeat), &view)
+            }
         }
-        if event == Event::Key(KeyEvent::new(KeyCode::Char('q'),KeyModifiers::CONTROL)){
-            println!("game quit!\r");
-            break;
+        match game_over(&session, profile)? {
+            AfterGame::Restart => continue,
+            after => return Ok(after)
         }
     }
-    Ok(()).expect("TODO: panic message");
 }
 
-fn flush_output() {
-    let _ = stdout().flush();
+fn print_events(mut config:GameConfig,controls:Controls,releases:bool,theme:Theme) -> std::io::Result<()> {
+    // config.mode 和 config.start_level 记住上一次 Marathon 的选择
+    let mut selected = MenuItem::Marathon;
+    let (scores, mut notice) = match HighScores::load() {
+        Ok(scores) => (scores, String::new()),
+        Err(e) => (HighScores::default(), format!("High scores not loaded: {}", e))
+    };
+    let mut profile = Profile{
+        name:env::var("USER").unwrap_or_else(|_| "Player".to_owned()),
+        scores,
+        controls,
+        releases,
+        theme
+    };
+    loop {
+        selected = main_menu(selected, &notice)?;
+        notice.clear();
+        match selected {
+            MenuItem::Marathon | MenuItem::Sprint | MenuItem::Ultra => {
+                let (mode, start_level) = match selected {
+                    MenuItem::Sprint => (GameMode::sprint(), constant::MIN_START_LEVEL),
+                    MenuItem::Ultra => match select("ULTRA", &["2 minutes", "3 minutes"], 0, "")? {
+                        Some(index) => (GameMode::ultra(2 + index as u64), constant::MIN_START_LEVEL),
+                        None => continue
+                    },
+                    _ => {
+                        if !settings::marathon(&mut config)? {
+                            continue;
+                        }
+                        (config.mode, config.start_level)
+                    }
+                };
+                if play(&GameConfig{mode, start_level, ..config.clone()}, &mut profile)? == AfterGame::Quit {
+                    break;
+                }
+            }
+            MenuItem::HighScores => scores::browse(&profile.scores)?,
+            MenuItem::Settings => settings::edit(&mut config)?,
+            MenuItem::Quit => break
+        }
+    }
+    clear();
+    let _ = terminal::goto(0,0);
+    Ok(())
 }
 
-fn main() {
-    println!("{}", HELP);
+fn main() -> std::io::Result<()> {
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, cli::USAGE);
+            exit(2);
+        }
+    };
+    let controls = match Controls::load() {
+        Ok(controls) => controls,
+        Err(e) => {
+            eprintln!("Invalid controls: {}", e);
+            exit(2);
+        }
+    };
     enable_raw_mode()?;
     let mut stdout = stdout();
-    execute!(stdout,EnableMouseCapture)?;
-    if let Err(e) = print_events(){
+    execute!(stdout,EnableMouseCapture,EnableFocusChange)?;
+    // 能报告按键抬起的终端才用得上 DAS，Windows 控制台本来就会报告
+    let enhanced = supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced {
+        execute!(stdout,PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+        ))?;
+    }
+    if let Err(e) = print_events(options.config, controls, enhanced || cfg!(windows), options.theme){
         println!("Error : {:?}\r",e)
     }
-    execute!(stdout,DisableMouseCapture)?;
-    disable_raw_mode().expect("TODO: panic message");
-}
-
-
-fn goto(x: u16, y: u16) -> Result<()> {
-    execute!(stdout(), MoveTo(x, y))?;
-    Ok(())
+    if enhanced {
+        execute!(stdout,PopKeyboardEnhancementFlags)?;
+    }
+    execute!(stdout,Show,DisableFocusChange,DisableMouseCapture)?;
+    disable_raw_mode()
 }
-
-fn clear() {
-    let _ = execute!(stdout(),Clear(ClearType::All));
-}
\ No newline at end of file
@@ -0,0 +1,214 @@
+use crate::model::board::Board;
+use crate::model::canvas::Canvas;
+use crate::model::cell::Cell;
+use crate::model::coordinate::Coordinate;
+
+/// A board that only keeps whether each cell is taken, one bit per cell and one
+/// `u64` per row, for bots and replays that simulate many placements. Bit `x`
+/// of a row is column `x`, walls included, so boards up to 62 columns fit.
+///
+/// It forgets which piece left each block; the game itself plays on a
+/// [`Canvas`], and `BitBoard::from(game.canvas())` gives the locked stack
+/// without the falling piece.
+#[derive(Debug,Clone,Eq,PartialEq)]
+pub struct BitBoard{
+    rows:Vec<u64>,
+    width:i32,
+    hidden:i32
+}
+
+impl BitBoard {
+    /// An empty board, sized the same way as [`Canvas::new`].
+    pub fn new(width:usize,height:usize,hidden:usize) -> Self {
+        let width = width as i32 + 2;
+        assert!(width <= 64, "a bit board holds at most 62 columns");
+        let mut board = BitBoard{rows:Vec::new(), width, hidden:hidden as i32};
+        board.rows = vec![board.walls(); hidden + height];
+        board.rows.push(board.full());
+        board
+    }
+
+    /// Row `y` as a bit mask. Rows off the board are all taken.
+    pub fn row(&self,y:i32) -> u64 {
+        if y < 0 || y >= self.height() {
+            return u64::MAX;
+        }
+        self.rows[y as usize]
+    }
+
+    /// A row with every column taken.
+    fn full(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+
+    /// An empty row, with only the two walls.
+    fn walls(&self) -> u64 {
+        1 | 1 << (self.width - 1)
+    }
+}
+
+impl Board for BitBoard {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.rows.len() as i32
+    }
+
+    fn hidden(&self) -> i32 {
+        self.hidden
+    }
+
+    fn is_occupied(&self,x:i32,y:i32) -> bool {
+        x < 0 || x >= self.width || self.row(y) & 1 << x != 0
+    }
+
+    fn set(&mut self,x:i32,y:i32,cell:Cell) {
+        let row = &mut self.rows[y as usize];
+        if cell.is_occupied() {
+            *row |= 1 << x;
+        } else {
+            *row &= !(1 << x);
+        }
+    }
+
+    fn is_row_full(&self,y:i32) -> bool {
+        self.row(y) & self.full() == self.full()
+    }
+
+    fn clear_row(&mut self,y:i32) {
+        self.rows.remove(y as usize);
+        self.rows.insert(0, self.walls());
+    }
+
+    /// Builds a mask for each row the blocks touch and tests it against the
+    /// row in one AND.
+    fn collides(&self,blocks:&[Coordinate]) -> bool {
+        let mut masks:[(i32,u64);4] = [(0, 0); 4];
+        let mut count = 0;
+        for p in blocks {
+            if p.x < 0 || p.x >= self.width {
+                return true;
+            }
+            match masks[.. count].iter_mut().find(|(y, _)| *y == p.y) {
+                Some((_, mask)) => *mask |= 1 << p.x,
+                None if count < 4 => {
+                    masks[count] = (p.y, 1 << p.x);
+                    count += 1;
+                }
+                None => if self.row(p.y) & 1 << p.x != 0 {
+                    return true;
+                }
+            }
+        }
+        masks[.. count].iter().any(|(y, mask)| self.row(*y) & mask != 0)
+    }
+}
+
+impl From<&Canvas> for BitBoard {
+    fn from(canvas:&Canvas) -> Self {
+        assert!(canvas.width() <= 64, "a bit board holds at most 62 columns");
+        let rows = canvas.rows().map(|line| {
+            line.iter().enumerate().filter(|(_, cell)| cell.is_occupied()).fold(0, |row, (x, _)| row | 1 << x)
+        }).collect();
+        BitBoard{rows, width:canvas.width(), hidden:canvas.hidden()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::canvas_piece::PieceType;
+
+    /// A canvas and a bit board with the same stack, built from `seed`: the
+    /// bottom rows are mostly filled and a few of them completely.
+    fn boards(width:usize,seed:u64) -> (Canvas,BitBoard) {
+        let mut canvas = Canvas::new(width, 20, 2);
+        let mut board = BitBoard::new(width, 20, 2);
+        let mut state = seed;
+        for y in 12 .. canvas.height() - 1 {
+            let full = y % 3 == 0;
+            for x in 1 .. canvas.width() - 1 {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                if full || state >> 62 != 0 {
+                    let cell = if x % 2 == 0 { Cell::Garbage } else { Cell::Locked(PieceType::T) };
+                    canvas.set(x, y, cell);
+                    board.set(x, y, cell);
+                }
+            }
+        }
+        (canvas, board)
+    }
+
+    fn assert_same(canvas:&Canvas,board:&BitBoard) {
+        assert_eq!(canvas.height(), board.height());
+        for y in -1 ..= canvas.height() {
+            assert_eq!(canvas.is_row_full(y), board.is_row_full(y), "row {}", y);
+            for x in -1 ..= canvas.width() {
+                assert_eq!(canvas.is_occupied(x, y), board.is_occupied(x, y), "({}, {})", x, y);
+            }
+        }
+        assert_eq!(&BitBoard::from(canvas), board);
+    }
+
+    #[test]
+    fn starts_the_same() {
+        for width in [4, 10, 40] {
+            let (canvas, board) = boards(width, 0);
+            assert_same(&canvas, &board);
+            assert_same(&Canvas::new(width, 20, 2), &BitBoard::new(width, 20, 2));
+        }
+    }
+
+    #[test]
+    fn collides_the_same() {
+        for seed in 0 .. 20 {
+            let (canvas, board) = boards(10, seed);
+            for x in -2 .. canvas.width() + 1 {
+                for y in -2 .. canvas.height() + 1 {
+                    // 一横一竖，跨行也跨列
+                    let blocks = [
+                        Coordinate{x, y},
+                        Coordinate{x:x + 1, y},
+                        Coordinate{x, y:y + 1},
+                        Coordinate{x, y:y + 2},
+                    ];
+                    assert_eq!(canvas.collides(&blocks), board.collides(&blocks), "{:?}", blocks);
+                    assert_eq!(canvas.collides(&blocks[.. 1]), board.collides(&blocks[.. 1]));
+                }
+            }
+            assert!(!board.collides(&[]));
+        }
+    }
+
+    #[test]
+    fn clears_rows_the_same() {
+        for seed in 0 .. 20 {
+            let (mut canvas, mut board) = boards(10, seed);
+            let full:Vec<i32> = (0 .. canvas.height() - 1).filter(|y| canvas.is_row_full(*y)).collect();
+            assert!(full.len() >= 2);
+            // 和 success 一样从上往下清，一次清好几行
+            for y in full {
+                canvas.clear_row(y);
+                board.clear_row(y);
+                assert_same(&canvas, &board);
+            }
+            assert!((0 .. canvas.height() - 1).all(|y| !board.is_row_full(y)));
+        }
+    }
+
+    #[test]
+    fn clears_the_bottom_row_under_a_stack() {
+        let (mut canvas, mut board) = boards(4, 7);
+        let floor = canvas.height() - 1;
+        for x in 1 .. canvas.width() - 1 {
+            canvas.set(x, floor - 1, Cell::Garbage);
+            board.set(x, floor - 1, Cell::Garbage);
+        }
+        canvas.clear_row(floor - 1);
+        board.clear_row(floor - 1);
+        assert_same(&canvas, &board);
+        assert!(canvas.is_row_full(floor) && board.is_row_full(floor));
+    }
+}
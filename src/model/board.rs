@@ -0,0 +1,33 @@
+use crate::model::cell::Cell;
+use crate::model::coordinate::Coordinate;
+
+/// What the piece logic needs from a board: which cells are taken, and how to
+/// fill cells and clear rows. Coordinates count the walls and the floor, so
+/// `(0, y)` is the left wall and `height() - 1` the floor.
+pub trait Board {
+    /// Width in cells, walls included.
+    fn width(&self) -> i32;
+
+    /// Height in cells, hidden rows and floor included.
+    fn height(&self) -> i32;
+
+    /// How many of the top rows are the vanish zone above the visible field.
+    fn hidden(&self) -> i32;
+
+    /// Whether `(x, y)` is taken. Anything off the board counts as taken.
+    fn is_occupied(&self,x:i32,y:i32) -> bool;
+
+    fn set(&mut self,x:i32,y:i32,cell:Cell);
+
+    /// Whether every cell between the walls of row `y` is filled.
+    fn is_row_full(&self,y:i32) -> bool;
+
+    /// Removes row `y` and shifts everything above it down one row, leaving an
+    /// empty row at the top.
+    fn clear_row(&mut self,y:i32);
+
+    /// Whether any of `blocks` is taken.
+    fn collides(&self,blocks:&[Coordinate]) -> bool {
+        blocks.iter().any(|p| self.is_occupied(p.x, p.y))
+    }
+}
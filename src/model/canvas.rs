@@ -1,5 +1,99 @@
-use crate::model::canvas_pixel::CanvasPixel;
-#[derive(Debug)]
+use crate::constant::constant;
+use crate::model::board::Board;
+use crate::model::cell::Cell;
+
+/// The board, walls and floor included, as one row-major grid of cells.
+#[derive(Debug,Clone)]
 pub struct Canvas{
-    pub pixels:Vec<Vec<CanvasPixel>>
-}
\ No newline at end of file
+    cells:Vec<Cell>,
+    width:i32,
+    height:i32,
+    /// How many of the top rows are the vanish zone above the visible field.
+    hidden:i32
+}
+
+impl Canvas {
+    /// An empty board with a visible playfield of `width` columns by `height`
+    /// rows, topped by `hidden` rows of vanish zone. The left and right walls and
+    /// the floor are generated around it, so the canvas is two columns wider and
+    /// `hidden + 1` rows taller than the visible field.
+    pub fn new(width:usize,height:usize,hidden:usize) -> Self {
+        let height = (hidden + height) as i32 + 1;
+        let width = width as i32 + 2;
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for y in 0 .. height {
+            for x in 0 .. width {
+                let wall = y == height - 1 || x == 0 || x == width - 1;
+                cells.push(if wall { Cell::Wall } else { Cell::Empty });
+            }
+        }
+        Canvas{
+            cells,
+            width,
+            height,
+            hidden:hidden as i32
+        }
+    }
+
+    /// The cell at `(x, y)`. Anything off the canvas counts as wall.
+    pub fn get(&self,x:i32,y:i32) -> Cell {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return Cell::Wall;
+        }
+        self.cells[(y * self.width + x) as usize]
+    }
+
+    /// Every row from the top of the vanish zone down to the floor.
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width as usize)
+    }
+
+    /// The rows a player sees, floor included.
+    pub fn visible(&self) -> impl Iterator<Item = &[Cell]> {
+        self.rows().skip(self.hidden as usize)
+    }
+}
+
+impl Board for Canvas {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn hidden(&self) -> i32 {
+        self.hidden
+    }
+
+    fn is_occupied(&self,x:i32,y:i32) -> bool {
+        self.get(x, y).is_occupied()
+    }
+
+    fn set(&mut self,x:i32,y:i32,cell:Cell) {
+        self.cells[(y * self.width + x) as usize] = cell;
+    }
+
+    fn is_row_full(&self,y:i32) -> bool {
+        (1 .. self.width - 1).all(|x| self.is_occupied(x, y))
+    }
+
+    fn clear_row(&mut self,y:i32) {
+        let width = self.width as usize;
+        self.cells.copy_within(0 .. y as usize * width, width);
+        for x in 1 .. self.width - 1 {
+            self.set(x, 0, Cell::Empty);
+        }
+    }
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Canvas::new(
+            (constant::CANVAS_WIDTH - 2) as usize,
+            (constant::CANVAS_HEIGHT - 1) as usize,
+            constant::HIDDEN_ROWS
+        )
+    }
+}
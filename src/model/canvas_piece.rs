@@ -1,26 +1,46 @@
-use std::fmt::Pointer;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use crate::enumerate::Color;
-use crate::model::canvas::Canvas;
-use crate::model::canvas_pixel::CanvasPixel;
-use crate::common::transform_symbol;
-use crate::constant::constant;
+use crate::model::board::Board;
+use crate::model::cell::Cell;
+use crate::model::coordinate::Coordinate;
+use crate::model::piece_generator::PieceGenerator;
+use crate::model::rotation;
 
-#[derive(Eq,PartialEq)]
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Default)]
 pub enum Direction {
+    #[default]
     Up,
     Right,
     Down,
     Left
 }
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::Up
+
+impl Direction {
+    pub fn clockwise(&self) -> Direction {
+        match self {
+            Direction::Up => {Direction::Right}
+            Direction::Right => {Direction::Down}
+            Direction::Down => {Direction::Left}
+            Direction::Left => {Direction::Up}
+        }
+    }
+
+    pub fn counter_clockwise(&self) -> Direction {
+        match self {
+            Direction::Up => {Direction::Left}
+            Direction::Left => {Direction::Down}
+            Direction::Down => {Direction::Right}
+            Direction::Right => {Direction::Up}
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        self.clockwise().clockwise()
     }
 }
 
-#[derive(Eq,PartialEq)]
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
 pub enum PieceType{
     I = 1,
     O = 2,
@@ -32,10 +52,30 @@ pub enum PieceType{
 }
 
 impl PieceType{
-    pub fn next() -> Self{
-        return rand::random();
+    pub const ALL:[PieceType;7] = [
+        PieceType::I,
+        PieceType::O,
+        PieceType::T,
+        PieceType::J,
+        PieceType::L,
+        PieceType::S,
+        PieceType::Z
+    ];
+
+    /// The Guideline color of the piece.
+    pub fn color(&self) -> Color {
+        match self {
+            PieceType::I => Color::Cyan,
+            PieceType::O => Color::Yellow,
+            PieceType::T => Color::Purple,
+            PieceType::J => Color::Blue,
+            PieceType::L => Color::Orange,
+            PieceType::S => Color::Green,
+            PieceType::Z => Color::Red
+        }
     }
 }
+
 impl Distribution<PieceType> for Standard{
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> PieceType {
         match rng.gen_range(1,8) {
@@ -52,465 +92,191 @@ impl Distribution<PieceType> for Standard{
 }
 
 
+#[derive(Debug,Clone)]
 pub struct CanvasPiece{
     pub direction:Direction,
     pub r#type:PieceType,
-    pub canvas:Option<Canvas>,
     pub x:i32,
     pub y:i32,
-    pub pixels:Vec<CanvasPixel>
+    /// The squares the piece covers on the canvas.
+    pub blocks:[Coordinate;4]
 }
 
 impl CanvasPiece {
-    pub fn next(canvas:Option<Canvas>) -> Self {
-        let piece_type = PieceType::next();
-        let mut pixels = Vec::with_capacity(4);
-        match piece_type {
-            PieceType::I => {
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -3
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -4
-                });
+    pub fn next(generator:&mut dyn PieceGenerator,canvas:&impl Board) -> Self {
+        Self::spawn(generator.next(), canvas)
+    }
 
-            }
-            PieceType::T => {
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X - 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X + 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                })
-            }
-            PieceType::Z => {
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X + 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X - 1,
-                    y: -2
-                })
-            }
-            PieceType::S => {
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X - 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X + 1,
-                    y: -2
-                });
-            }
-            PieceType::O => {
-                // O 不管，不能转也行，毕竟转了也是这样
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X - 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X - 1,
-                    y: -2
-                });
-            }
-            PieceType::L => {
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X + 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -3
-                });
-            }
-            PieceType::J => {
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X - 1,
-                    y: -1
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -2
-                });
-                pixels.push(CanvasPixel {
-                    color: Color::Green,
-                    symbol: transform_symbol("□"),
-                    x: constant::INIT_COORDINATE_X,
-                    y: -3
-                });
-            }
-        }
+    /// A piece in the two hidden rows just above the visible field, centered and
+    /// rounding to the left on odd widths.
+    pub fn spawn(piece_type:PieceType,canvas:&impl Board) -> Self {
+        let x = canvas.width() / 2 - 2;
+        let y = canvas.hidden() - 2;
         CanvasPiece{
             direction:Direction::Up,
             r#type:piece_type,
-            x:constant::INIT_COORDINATE_X,
-            y:constant::INIT_COORDINATE_Y,
-            pixels,canvas,
+            x,
+            y,
+            blocks:Self::blocks_at(piece_type, Direction::Up, x, y),
         }
     }
-    pub fn in_self(&self,pixel:&CanvasPixel) -> bool {
-        for p in self.pixels.iter() {
-            if p.x == pixel.x && p.y == pixel.y {
-                return true
-            }
-        }
-        false
+
+    fn blocks_at(piece_type:PieceType,direction:Direction,x:i32,y:i32) -> [Coordinate;4] {
+        rotation::shape(piece_type, direction).map(|(dx, dy)| Coordinate{
+            x:x + dx,
+            y:y + dy
+        })
     }
 
-    pub fn can_rotate(&self) -> bool {
-        if self.y <= constant::INIT_COORDINATE_Y || self.r#type == PieceType::O{
-            return false
-        }
-        let canvas = self.canvas.as_ref().unwrap();
-        for p in self.pixels.iter(){
-            let j = self.y - p.y + self.x;
-            let k = p.x - self.x + self.y;
-            if j <= 0 || j >= constant::CANVAS_WIDTH - 1 {
-                return false;
-            }
-            if k >= constant::CANVAS_HEIGHT - 1 {
-                return false;
-            }
-            if k >= 0 {
-                let line = canvas.pixels.get(k as usize).unwrap();
-                if !self.in_self(line.get(j as usize).unwrap()) && line.get(j as usize).unwrap().symbol == transform_symbol("□") {
-                    return false;
-                }
-            }
-        }
-        return true;
+    /// The row of the piece's lowest block.
+    pub fn lowest_row(&self) -> i32 {
+        self.blocks.iter().map(|p| p.y).max().unwrap_or(self.y)
     }
 
-    pub fn rotate(&mut self) {
-        if self.can_rotate() {
-            for p in self.pixels.iter() {
-                if p.y >= 0 {
-                    let line:&mut Vec<CanvasPixel>= self.canvas.as_mut().unwrap().pixels.get_mut(p.y as usize).unwrap();
-                    line[p.x as usize] = CanvasPixel{
-                        color:Color::Green,
-                        symbol:transform_symbol(" "),
-                        x:p.x,
-                        y:p.y
-                    }
-                }
-            }
-            for p in self.pixels.as_mut() {
-                let j = self.y - p.y + self.x;
-                let k = p.x - self.x + self.y;
-                p.x = j;
-                p.y = k;
-                if k >= 0 {
-                    let line:&mut Vec<CanvasPixel> = self.canvas.as_mut().unwrap().pixels.get_mut(k as usize).unwrap();
-                    line[j as usize] = CanvasPixel{
-                        color:Color::Green,
-                        symbol:transform_symbol("□"),
-                        x:j,
-                        y:k
-                    }
-                }
-            }
-            self.canvas.as_ref().unwrap().show_self();
-            self.direction = Self::next_direction(&self.direction);
-        }
+    pub fn in_self(&self,x:i32,y:i32) -> bool {
+        self.blocks.iter().any(|p| p.x == x && p.y == y)
     }
-    pub fn can_drop_down(&self) -> bool {
-        let canvas = self.canvas.as_ref().unwrap();
-        for p in self.pixels.iter(){
-            if p.y >= -1 && canvas.pixels.get((p.y + 1) as usize).unwrap().get(p.x as usize).unwrap().symbol
-                == transform_symbol("□") {
-                return false
+
+    /// The piece's blocks moved by `(dx, dy)`.
+    fn moved(&self,dx:i32,dy:i32) -> [Coordinate;4] {
+        self.blocks.map(|p| Coordinate{x:p.x + dx, y:p.y + dy})
+    }
+
+    /// Whether every block of the piece is in the hidden rows (Guideline lock out).
+    pub fn above_visible(&self,canvas:&impl Board) -> bool {
+        self.blocks.iter().all(|p| p.y < canvas.hidden())
+    }
+
+    /// Whether the piece overlaps blocks already on the canvas (Guideline block out).
+    pub fn blocked(&self,canvas:&impl Board) -> bool {
+        canvas.collides(&self.blocks)
+    }
+
+    /// Tries the SRS kicks for rotating to `direction` and returns the index of
+    /// the first kick test at which the rotated piece fits.
+    pub fn can_rotate(&self,canvas:&impl Board,direction:Direction) -> Option<usize> {
+        self.find_kick(canvas, direction).map(|(kick, _)| kick)
+    }
+
+    /// The first kick test that fits, with the offset it moves the piece by.
+    fn find_kick(&self,canvas:&impl Board,direction:Direction) -> Option<(usize,(i32,i32))> {
+        let shape = rotation::shape(self.r#type, direction);
+        rotation::kicks(self.r#type, self.direction, direction).iter().copied().enumerate().find(|(_, (kx, ky))| {
+            !canvas.collides(&shape.map(|(dx, dy)| Coordinate{x:self.x + kx + dx, y:self.y + ky + dy}))
+        })
+    }
+
+    /// Rotates the piece if any kick test fits, returning the index of the test used.
+    pub fn rotate(&mut self,canvas:&impl Board,direction:Direction) -> Option<usize> {
+        let (kick, (kx, ky)) = self.find_kick(canvas, direction)?;
+        self.direction = direction;
+        self.x += kx;
+        self.y += ky;
+        self.blocks = Self::blocks_at(self.r#type, self.direction, self.x, self.y);
+        Some(kick)
+    }
+
+    /// Counts the occupied diagonal corners around a T piece's center, split into
+    /// the two on the side its point faces and the two behind it. Walls and the
+    /// floor count as occupied.
+    pub fn t_corners(&self,canvas:&impl Board) -> (usize,usize) {
+        let occupied = |(dx, dy):(i32,i32)| {
+            let x = self.x + 1 + dx;
+            let y = self.y + 1 + dy;
+            if x <= 0 || x >= canvas.width() - 1 {
+                return true;
             }
+            y >= 0 && canvas.is_occupied(x, y)
+        };
+        let (front, back) = match self.direction {
+            Direction::Up => ([(-1,-1),(1,-1)], [(-1,1),(1,1)]),
+            Direction::Right => ([(1,-1),(1,1)], [(-1,-1),(-1,1)]),
+            Direction::Down => ([(-1,1),(1,1)], [(-1,-1),(1,-1)]),
+            Direction::Left => ([(-1,-1),(-1,1)], [(1,-1),(1,1)]),
+        };
+        (
+            front.into_iter().filter(|c| occupied(*c)).count(),
+            back.into_iter().filter(|c| occupied(*c)).count()
+        )
+    }
+
+    pub fn can_drop_down(&self,canvas:&impl Board) -> bool {
+        !canvas.collides(&self.moved(0, 1))
+    }
+
+    /// Moves the piece one row down. Returns `false` when it has landed and should be locked.
+    pub fn drop_down(&mut self,canvas:&impl Board) -> bool {
+        if !self.can_drop_down(canvas) {
+            return false;
         }
+        for p in self.blocks.iter_mut() {
+            p.y += 1;
+        }
+        self.y += 1;
         true
     }
 
-    pub fn drop_down(&mut self) {
-        if self.can_drop_down() {
-            let canvas = self.canvas.as_mut().unwrap();
-            for p in self.pixels.as_mut() as &mut Vec<CanvasPixel> {
-                if p.y >= 0 {
-                    let line:&mut Vec<CanvasPixel> = canvas.pixels.get_mut(p.y as usize).unwrap();
-                    line[p.x as usize] = CanvasPixel{
-                        color:Color::Green,
-                        symbol:transform_symbol(" "),
-                        x:p.x,
-                        y:p.y
-                    };
-                }
-                p.y = p.y + 1
-            }
-
-            for p in self.pixels.iter() {
-                if p.y >= 0 {
-                    let line:&mut Vec<CanvasPixel> = canvas.pixels.get_mut(p.y as usize).unwrap();
-                    line[p.x as usize] = CanvasPixel{
-                        color:Color::Green,
-                        symbol:transform_symbol("□"),
-                        x:p.x,
-                        y:p.y
-                    };
-                }
-            }
-            self.y = self.y + 1;
-            canvas.show_self();
-        } else {
-            for p in self.pixels.iter() {
-                if p.y == constant::INIT_COORDINATE_Y {
-                    let canvas = self.canvas.as_mut().unwrap();
-                    canvas.failure();
-                }
-            }
-            self.success();
-            let tmp = CanvasPiece::next(None);
-            self.direction = tmp.direction;
-            self.r#type = tmp.r#type;
-            self.x = tmp.x;
-            self.y = tmp.y;
-            self.pixels = tmp.pixels;
+    /// How many rows the piece can fall before it lands, found by repeating the
+    /// `can_drop_down` check further and further down without touching the canvas.
+    pub fn drop_distance(&self,canvas:&impl Board) -> i32 {
+        let mut distance = 0;
+        while !canvas.collides(&self.moved(0, distance + 1)) {
+            distance += 1;
         }
+        distance
     }
 
-    pub fn success(&mut self) {
-        let canvas = self.canvas.as_mut().unwrap();
-        self.pixels.sort_by(|a, b| a.y.partial_cmp(&b.y).unwrap());
-        for p in self.pixels.iter() {
-            let line = canvas.pixels.get_mut(p.y as usize).unwrap() as &mut Vec<CanvasPixel>;
-            let mut flag = true;
-            for x in 1 .. line.len() - 1 {
-                if line.get(x).unwrap().symbol == transform_symbol(" ") {
-                    flag = false;
-                    break;
-                }
-            }
-            if flag {
-                for x in 1 .. line.len() - 1 {
-                    line[x] = CanvasPixel {
-                        color:Color::Green,
-                        symbol:transform_symbol(" "),
-                        x:x as i32,
-                        y:p.y
-                    };
-                }
-                if p.y >= 1 {
-                    let mut ln = p.y - 1 ;
-                    while ln >= 0 {
-                        let pixels = canvas.pixels.as_mut() as &mut Vec<Vec<CanvasPixel>>;
-                        let line = pixels.get(ln as usize).unwrap().clone();
-                        let line2 = pixels.get_mut(ln as usize).unwrap().clone();
-                        for x in 1 .. line.len() - 1 {
-                            line2[x] = CanvasPixel{
-                                color:Color::Green,
-                                symbol:line.get(x).unwrap().symbol.clone(),
-                                x:x as i32,
-                                y:ln
-                            };
-                        }
-                        ln -= -1;
-                    }
-                }
-                canvas.show_self();
+    /// Moves the piece straight to its landing row and returns how many rows it fell.
+    pub fn hard_drop(&mut self,canvas:&impl Board) -> i32 {
+        let distance = self.drop_distance(canvas);
+        if distance > 0 {
+            for p in self.blocks.iter_mut() {
+                p.y += distance;
             }
+            self.y += distance;
         }
+        distance
     }
 
-    pub fn can_horizontal_move(&self,move_left:bool) -> bool {
-        let canvas = self.canvas.as_ref().unwrap();
-        return if move_left {
-            for p in self.pixels.iter(){
-                if p.y >= 0 {
-                    let line = canvas.pixels.get(p.y as usize).unwrap();
-                    let pixel = line.get(p.x as usize - 1).unwrap();
-                    if !self.in_self(pixel)  && pixel.symbol == transform_symbol("□"){
-                        return false;
-                    }
-                }
-                if p.x <= 1 {
-                    return false;
-                }
-            }
-            return true;
-        }else {
-            for p in self.pixels.iter() {
-                if p.y >= 0 {
-                    let line = canvas.pixels.get(p.y as usize).unwrap();
-                    let pixel = line.get(p.x as usize + 1).unwrap();
-                    if !self.in_self(pixel) && pixel.symbol == transform_symbol("□") {
-                        return false;
-                    }
-                }
-                if p.x >= constant::CANVAS_WIDTH - 2 {
-                    return false
-                }
+    /// Clears every full row the piece landed on, once it has been stamped, and
+    /// shifts the rows above it down, keeping the pieces of the blocks that move.
+    /// Returns how many rows were cleared.
+    pub fn success(&mut self,canvas:&mut impl Board) -> usize {
+        let mut rows:Vec<i32> = self.blocks.iter().map(|p| p.y).collect();
+        rows.sort();
+        rows.dedup();
+        let mut cleared = 0;
+        // 从上往下清，下面的行号不受影响
+        for y in rows {
+            if canvas.is_row_full(y) {
+                canvas.clear_row(y);
+                cleared += 1;
             }
-            return true;
         }
+        cleared
     }
 
-    pub fn horizontal_move(&mut self,move_left:bool) {
-        if self.can_horizontal_move(move_left) {
-            let canvas = self.canvas.as_mut().unwrap();
-            for p in self.pixels.as_mut() as &mut Vec<CanvasPixel> {
-                if p.y >= 0 {
-                    let line:&mut Vec<CanvasPixel> = canvas.pixels.get_mut(p.y as usize).unwrap();
-                    line[p.x as usize] = CanvasPixel{
-                        color:Color::Green,
-                        symbol:transform_symbol(" "),
-                        x:p.x,
-                        y:p.y
-                    };
-                }
-                if move_left {
-                    for p in self.pixels.as_mut() as &mut Vec<CanvasPixel> {
-                        p.x = -1;
-                        if p.y >= 0 {
-                            let line:&mut Vec<CanvasPixel> = canvas.pixels.get_mut(p.y as usize).unwrap();
-                            line[p.x as usize] = CanvasPixel{
-                                color:Color::Green,
-                                symbol:transform_symbol("□"),
-                                x:p.x,
-                                y:p.y
-                            };
-                        }
-                    }
-                    self.x = self.x - 1;
-                } else {
-                    for p in self.pixels.as_mut() as &mut Vec<CanvasPixel>{
-                        p.x += 1;
-                        if p.y >= 0 {
-                            let line:&mut Vec<CanvasPixel> = canvas.pixels.get_mut(p.y as usize).unwrap();
-                            line[p.x as usize] = CanvasPixel{
-                                color:Color::Green,
-                                symbol:transform_symbol("□"),
-                                x:p.x,
-                                y:p.y
-                            };
-                        }
-                    }
-                    self.x += 1;
-                }
-                canvas.show_self();
-            }
+    pub fn can_horizontal_move(&self,canvas:&impl Board,move_left:bool) -> bool {
+        let offset = if move_left { -1 } else { 1 };
+        !canvas.collides(&self.moved(offset, 0))
+    }
+
+    pub fn horizontal_move(&mut self,canvas:&impl Board,move_left:bool) -> bool {
+        if !self.can_horizontal_move(canvas, move_left) {
+            return false;
+        }
+        let offset = if move_left { -1 } else { 1 };
+        for p in self.blocks.iter_mut() {
+            p.x += offset;
         }
+        self.x += offset;
+        true
     }
 
-    fn next_direction(direction: &Direction) -> Direction {
-        match direction {
-            Direction::Up => {Direction::Right}
-            Direction::Right => {Direction::Down}
-            Direction::Down => {Direction::Left}
-            Direction::Left => {Direction::Up}
+    /// Writes the piece into `canvas` as locked blocks, once it has landed.
+    pub fn stamp(&self,canvas:&mut impl Board) {
+        for p in self.blocks.iter() {
+            canvas.set(p.x, p.y, Cell::Locked(self.r#type));
         }
     }
 }
-
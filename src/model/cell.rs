@@ -0,0 +1,22 @@
+use crate::model::canvas_piece::PieceType;
+
+/// What occupies one square of the board. Only the game rules look at cells;
+/// front ends decide how each kind is drawn.
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Default)]
+pub enum Cell{
+    #[default]
+    Empty,
+    /// The side walls and the floor around the playfield.
+    Wall,
+    /// A block left by a piece that locked.
+    Locked(PieceType),
+    /// A block that came from below rather than from a piece.
+    Garbage
+}
+
+impl Cell {
+    /// Whether a piece can't move into this cell.
+    pub fn is_occupied(&self) -> bool {
+        *self != Cell::Empty
+    }
+}
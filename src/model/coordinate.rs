@@ -1,4 +1,5 @@
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
 pub struct Coordinate {
     pub x:i32,
     pub y:i32
-}
\ No newline at end of file
+}
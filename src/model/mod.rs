@@ -1,65 +1,17 @@
-use std::process::exit;
-use crossterm::style;
-use crossterm::style::{style, Stylize};
-use crate::enumerate::Color;
-
-pub use canvas_pixel::CanvasPixel;
+pub use bit_board::BitBoard;
+pub use board::Board;
 pub use canvas::Canvas;
 pub use canvas_piece::{CanvasPiece, PieceType, Direction};
-use crate::{clear, goto};
+pub use cell::Cell;
+pub use piece_generator::{PieceGenerator, Randomizer, RandomGenerator, BagGenerator, HistoryGenerator};
 
-mod canvas_pixel;
+mod bit_board;
+mod board;
 mod canvas;
 mod canvas_piece;
+mod cell;
+mod piece_generator;
 mod coordinate;
+pub mod rotation;
 
-pub trait Colorable{
-    fn color(&self) -> &Color;
-}
-
-impl Colorable for CanvasPixel {
-    fn color(&self) -> &Color {
-        &self.color
-    }
-}
-
-pub trait ShowSelf{
-    fn show_self(&self);
-}
-
-impl ShowSelf for CanvasPixel {
-    fn show_self(&self) {
-        match &self.color {
-            Color::Red => {
-                print!("{}", style(format!("{}", self.symbol)).with(style::Color::Red))
-            }
-            Color::White => {
-                print!("{}", style(format!("{}", self.symbol)).with(style::Color::White))
-            }
-            Color::Green => {
-                print!("{}", style(format!("{}", self.symbol)).with(style::Color::Green))
-            }
-        }
-    }
-}
-
-impl ShowSelf for Canvas {
-    fn show_self(&self) {
-        let _ = goto(0,0);
-        for i in self.pixels.iter(){
-            for j in i.iter() {
-                j.show_self();
-            }
-            println!("\r")
-        }
-    }
-}
-
-impl Canvas {
-    pub fn failure(&self) {
-        clear();
-        let _= goto(0,0);
-        println!("game over");
-        exit(1);
-    }
-}
\ No newline at end of file
+pub use coordinate::Coordinate;
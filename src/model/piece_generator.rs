@@ -0,0 +1,184 @@
+use std::fmt::Debug;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::model::canvas_piece::PieceType;
+
+/// A source of upcoming pieces.
+pub trait PieceGenerator: Debug + Send {
+    fn next(&mut self) -> PieceType;
+
+    fn clone_box(&self) -> Box<dyn PieceGenerator>;
+}
+
+impl Clone for Box<dyn PieceGenerator> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The available generators, for picking one from a config.
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Default)]
+pub enum Randomizer{
+    Random,
+    #[default]
+    SevenBag,
+    History
+}
+
+impl Randomizer {
+    pub fn generator(&self,seed:u64) -> Box<dyn PieceGenerator> {
+        match self {
+            Randomizer::Random => Box::new(RandomGenerator::new(seed)),
+            Randomizer::SevenBag => Box::new(BagGenerator::new(seed)),
+            Randomizer::History => Box::new(HistoryGenerator::new(seed)),
+        }
+    }
+}
+
+/// Every piece is drawn independently, so droughts and floods are possible.
+#[derive(Debug,Clone)]
+pub struct RandomGenerator{
+    rng:StdRng
+}
+
+impl RandomGenerator {
+    pub fn new(seed:u64) -> Self {
+        RandomGenerator{
+            rng:StdRng::seed_from_u64(seed)
+        }
+    }
+}
+
+impl PieceGenerator for RandomGenerator {
+    fn next(&mut self) -> PieceType {
+        self.rng.gen()
+    }
+
+    fn clone_box(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+/// Deals all seven pieces in a shuffled bag before refilling it.
+#[derive(Debug,Clone)]
+pub struct BagGenerator{
+    rng:StdRng,
+    bag:Vec<PieceType>
+}
+
+impl BagGenerator {
+    pub fn new(seed:u64) -> Self {
+        BagGenerator{
+            rng:StdRng::seed_from_u64(seed),
+            bag:Vec::with_capacity(PieceType::ALL.len())
+        }
+    }
+}
+
+impl PieceGenerator for BagGenerator {
+    fn next(&mut self) -> PieceType {
+        if self.bag.is_empty() {
+            self.bag.extend_from_slice(&PieceType::ALL);
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.pop().unwrap()
+    }
+
+    fn clone_box(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+/// TGM style: rolls up to `HISTORY_ROLLS` times in total while the piece is one
+/// of the last four dealt, and never starts with an S, Z or O.
+#[derive(Debug,Clone)]
+pub struct HistoryGenerator{
+    rng:StdRng,
+    history:[PieceType;4],
+    first:bool
+}
+
+const HISTORY_ROLLS:usize = 4;
+
+impl HistoryGenerator {
+    pub fn new(seed:u64) -> Self {
+        HistoryGenerator{
+            rng:StdRng::seed_from_u64(seed),
+            history:[PieceType::Z;4],
+            first:true
+        }
+    }
+}
+
+impl PieceGenerator for HistoryGenerator {
+    fn next(&mut self) -> PieceType {
+        let piece_type = if self.first {
+            self.first = false;
+            *[PieceType::I, PieceType::T, PieceType::J, PieceType::L].choose(&mut self.rng).unwrap()
+        } else {
+            let mut piece_type = self.rng.gen();
+            for _ in 1 .. HISTORY_ROLLS {
+                if !self.history.contains(&piece_type) {
+                    break;
+                }
+                piece_type = self.rng.gen();
+            }
+            piece_type
+        };
+        self.history.rotate_right(1);
+        self.history[0] = piece_type;
+        piece_type
+    }
+
+    fn clone_box(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deal(randomizer:Randomizer,seed:u64,count:usize) -> Vec<PieceType> {
+        let mut generator = randomizer.generator(seed);
+        (0 .. count).map(|_| generator.next()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        for randomizer in [Randomizer::Random, Randomizer::SevenBag, Randomizer::History] {
+            assert_eq!(deal(randomizer, 42, 100), deal(randomizer, 42, 100), "{:?}", randomizer);
+            assert_ne!(deal(randomizer, 42, 100), deal(randomizer, 43, 100), "{:?}", randomizer);
+        }
+    }
+
+    #[test]
+    fn cloned_generator_continues_the_same() {
+        let mut generator = Randomizer::SevenBag.generator(7);
+        generator.next();
+        let mut clone = generator.clone();
+        for _ in 0 .. 20 {
+            assert_eq!(generator.next(), clone.next());
+        }
+    }
+
+    #[test]
+    fn bag_deals_every_piece_once_per_seven() {
+        for seed in 0 .. 20 {
+            for bag in deal(Randomizer::SevenBag, seed, 70).chunks(7) {
+                for piece_type in PieceType::ALL {
+                    assert_eq!(bag.iter().filter(|p| **p == piece_type).count(), 1, "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn history_never_starts_with_s_z_or_o() {
+        for seed in 0 .. 200 {
+            let first = HistoryGenerator::new(seed).next();
+            assert!(![PieceType::S, PieceType::Z, PieceType::O].contains(&first), "seed {}", seed);
+        }
+    }
+}
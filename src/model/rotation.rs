@@ -0,0 +1,189 @@
+//! Super Rotation System tables.
+//!
+//! Shapes are laid out in a bounding box whose top-left corner is the piece's
+//! `(x, y)`, with `y` growing downwards like the canvas rows. Kick offsets are
+//! written the way the SRS reference lists them, with `y` growing upwards, and
+//! flipped into canvas coordinates at compile time.
+
+use crate::model::canvas_piece::{Direction, PieceType};
+
+const I_SPAWN:[(i32,i32);4] = [(0,1),(1,1),(2,1),(3,1)];
+const O_SPAWN:[(i32,i32);4] = [(1,0),(2,0),(1,1),(2,1)];
+const T_SPAWN:[(i32,i32);4] = [(1,0),(0,1),(1,1),(2,1)];
+const J_SPAWN:[(i32,i32);4] = [(0,0),(0,1),(1,1),(2,1)];
+const L_SPAWN:[(i32,i32);4] = [(2,0),(0,1),(1,1),(2,1)];
+const S_SPAWN:[(i32,i32);4] = [(1,0),(2,0),(0,1),(1,1)];
+const Z_SPAWN:[(i32,i32);4] = [(0,0),(1,0),(1,1),(2,1)];
+
+const JLSTZ_KICKS:[[(i32,i32);5];8] = [
+    [(0,0),(-1,0),(-1,1),(0,-2),(-1,-2)],   // Up -> Right
+    [(0,0),(1,0),(1,-1),(0,2),(1,2)],       // Right -> Up
+    [(0,0),(1,0),(1,-1),(0,2),(1,2)],       // Right -> Down
+    [(0,0),(-1,0),(-1,1),(0,-2),(-1,-2)],   // Down -> Right
+    [(0,0),(1,0),(1,1),(0,-2),(1,-2)],      // Down -> Left
+    [(0,0),(-1,0),(-1,-1),(0,2),(-1,2)],    // Left -> Down
+    [(0,0),(-1,0),(-1,-1),(0,2),(-1,2)],    // Left -> Up
+    [(0,0),(1,0),(1,1),(0,-2),(1,-2)],      // Up -> Left
+];
+
+const I_KICKS:[[(i32,i32);5];8] = [
+    [(0,0),(-2,0),(1,0),(-2,-1),(1,2)],     // Up -> Right
+    [(0,0),(2,0),(-1,0),(2,1),(-1,-2)],     // Right -> Up
+    [(0,0),(-1,0),(2,0),(-1,2),(2,-1)],     // Right -> Down
+    [(0,0),(1,0),(-2,0),(1,-2),(-2,1)],     // Down -> Right
+    [(0,0),(2,0),(-1,0),(2,1),(-1,-2)],     // Down -> Left
+    [(0,0),(-2,0),(1,0),(-2,-1),(1,2)],     // Left -> Down
+    [(0,0),(1,0),(-2,0),(1,-2),(-2,1)],     // Left -> Up
+    [(0,0),(-1,0),(2,0),(-1,2),(2,-1)],     // Up -> Left
+];
+
+/// Kicks for 180 degree rotations, from the SRS+ extension. SRS itself has none.
+const FLIP_KICKS:[[(i32,i32);6];4] = [
+    [(0,0),(0,1),(1,1),(-1,1),(1,0),(-1,0)],     // Up -> Down
+    [(0,0),(1,0),(1,2),(1,1),(0,2),(0,1)],       // Right -> Left
+    [(0,0),(0,-1),(-1,-1),(1,-1),(-1,0),(1,0)],  // Down -> Up
+    [(0,0),(-1,0),(-1,2),(-1,1),(0,2),(0,1)],    // Left -> Right
+];
+
+const NO_KICKS:[(i32,i32);1] = [(0,0)];
+
+const JLSTZ_CANVAS_KICKS:[[(i32,i32);5];8] = flip_y(JLSTZ_KICKS);
+const I_CANVAS_KICKS:[[(i32,i32);5];8] = flip_y(I_KICKS);
+const FLIP_CANVAS_KICKS:[[(i32,i32);6];4] = flip_y(FLIP_KICKS);
+
+/// Turns a kick table with `y` growing upwards into one for canvas rows.
+const fn flip_y<const N:usize,const M:usize>(mut table:[[(i32,i32);N];M]) -> [[(i32,i32);N];M] {
+    let mut row = 0;
+    while row < M {
+        let mut kick = 0;
+        while kick < N {
+            table[row][kick].1 = -table[row][kick].1;
+            kick += 1;
+        }
+        row += 1;
+    }
+    table
+}
+
+/// The four cells of `piece_type` in the given rotation state, relative to the
+/// top-left corner of its bounding box.
+pub fn shape(piece_type:PieceType,direction:Direction) -> [(i32,i32);4] {
+    let (spawn, size) = match piece_type {
+        PieceType::I => (I_SPAWN, 4),
+        // O 转了也是这样
+        PieceType::O => return O_SPAWN,
+        PieceType::T => (T_SPAWN, 3),
+        PieceType::J => (J_SPAWN, 3),
+        PieceType::L => (L_SPAWN, 3),
+        PieceType::S => (S_SPAWN, 3),
+        PieceType::Z => (Z_SPAWN, 3),
+    };
+    let turns = match direction {
+        Direction::Up => 0,
+        Direction::Right => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+    };
+    spawn.map(|(mut x, mut y)| {
+        for _ in 0 .. turns {
+            (x, y) = (size - 1 - y, x);
+        }
+        (x, y)
+    })
+}
+
+/// The offsets to try, in order, when rotating `piece_type` from `from` to `to`,
+/// in canvas coordinates.
+pub fn kicks(piece_type:PieceType,from:Direction,to:Direction) -> &'static [(i32,i32)] {
+    if piece_type == PieceType::O {
+        return &NO_KICKS;
+    }
+    let flip = match (from, to) {
+        (Direction::Up, Direction::Down) => Some(0),
+        (Direction::Right, Direction::Left) => Some(1),
+        (Direction::Down, Direction::Up) => Some(2),
+        (Direction::Left, Direction::Right) => Some(3),
+        _ => None
+    };
+    if let Some(row) = flip {
+        return &FLIP_CANVAS_KICKS[row];
+    }
+    let row = match (from, to) {
+        (Direction::Up, Direction::Right) => 0,
+        (Direction::Right, Direction::Up) => 1,
+        (Direction::Right, Direction::Down) => 2,
+        (Direction::Down, Direction::Right) => 3,
+        (Direction::Down, Direction::Left) => 4,
+        (Direction::Left, Direction::Down) => 5,
+        (Direction::Left, Direction::Up) => 6,
+        (Direction::Up, Direction::Left) => 7,
+        _ => return &NO_KICKS,
+    };
+    let table:&'static [[(i32,i32);5];8] = match piece_type {
+        PieceType::I => &I_CANVAS_KICKS,
+        _ => &JLSTZ_CANVAS_KICKS,
+    };
+    &table[row]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarter_turn_kicks_are_flipped_srs() {
+        // SRS 0->R: (0,0) (-1,0) (-1,+1) (0,-2) (-1,-2)，y 朝上
+        assert_eq!(
+            kicks(PieceType::T, Direction::Up, Direction::Right),
+            [(0,0),(-1,0),(-1,-1),(0,2),(-1,2)]
+        );
+        assert_eq!(
+            kicks(PieceType::I, Direction::Up, Direction::Right),
+            [(0,0),(-2,0),(1,0),(-2,1),(1,-2)]
+        );
+        assert_eq!(
+            kicks(PieceType::I, Direction::Left, Direction::Up),
+            [(0,0),(1,0),(-2,0),(1,2),(-2,-1)]
+        );
+    }
+
+    #[test]
+    fn reverse_turns_undo_each_other() {
+        let turns = [
+            (Direction::Up, Direction::Right),
+            (Direction::Right, Direction::Down),
+            (Direction::Down, Direction::Left),
+            (Direction::Left, Direction::Up),
+        ];
+        for piece_type in [PieceType::T, PieceType::I] {
+            for (from, to) in turns {
+                let forward = kicks(piece_type, from, to);
+                let back:Vec<(i32,i32)> = kicks(piece_type, to, from).iter().map(|(x, y)| (-x, -y)).collect();
+                assert_eq!(forward, back, "{:?} {:?} -> {:?}", piece_type, from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn o_and_flips() {
+        assert_eq!(kicks(PieceType::O, Direction::Up, Direction::Right), [(0,0)]);
+        assert_eq!(kicks(PieceType::O, Direction::Up, Direction::Down), [(0,0)]);
+        let flip = kicks(PieceType::T, Direction::Up, Direction::Down);
+        assert_eq!(flip.len(), 6);
+        assert_eq!(flip[1], (0,-1));
+        assert_eq!(flip, FLIP_KICKS[0].map(|(x, y)| (x, -y)));
+        assert_eq!(kicks(PieceType::T, Direction::Up, Direction::Up), [(0,0)]);
+    }
+
+    #[test]
+    fn shapes_stay_in_their_box() {
+        for piece_type in PieceType::ALL {
+            let size = if piece_type == PieceType::I { 4 } else { 3 };
+            for direction in [Direction::Up, Direction::Right, Direction::Down, Direction::Left] {
+                assert!(shape(piece_type, direction).iter().all(|(x, y)| (0 .. size).contains(x) && (0 .. size).contains(y)));
+            }
+        }
+        // T 朝右时尖朝右
+        assert_eq!(shape(PieceType::T, Direction::Right), [(2,1),(1,0),(1,1),(1,2)]);
+    }
+}
@@ -0,0 +1,268 @@
+use std::time::Duration;
+use tetris::constant::constant;
+use tetris::game::Input;
+use crate::terminal::controls::Handling;
+
+/// Without release events, presses closer together than this are the terminal
+/// repeating a held key, and a held key counts as released once its repeats
+/// stop for this long.
+const REPEAT_TIMEOUT:Duration = Duration::from_millis(100);
+/// Without release events, how long a key that hasn't repeated yet is kept,
+/// long enough to cover the usual system repeat delays.
+const FIRST_REPEAT_TIMEOUT:Duration = Duration::from_millis(700);
+
+#[derive(Debug)]
+struct Held{
+    input:Input,
+    /// How long the key has been down.
+    held:Duration,
+    /// Time carried over towards the next repeat.
+    carry:Duration,
+    /// Whether the key has started repeating.
+    repeating:bool,
+    /// Time since the terminal last reported the key.
+    quiet:Duration,
+    /// Whether the terminal repeated the key, which shows it is really held.
+    confirmed:bool
+}
+
+impl Held {
+    fn new(input:Input) -> Self {
+        Held{input, held:Duration::ZERO, carry:Duration::ZERO, repeating:false, quiet:Duration::ZERO, confirmed:false}
+    }
+
+    fn restart(&mut self) {
+        *self = Held{confirmed:self.confirmed, quiet:self.quiet, ..Held::new(self.input)};
+    }
+
+    fn expired(&self) -> bool {
+        self.quiet > if self.confirmed { REPEAT_TIMEOUT } else { FIRST_REPEAT_TIMEOUT }
+    }
+}
+
+/// Turns held movement and soft drop keys into repeated inputs at the
+/// configured DAS and ARR, instead of relying on the terminal's key repeat.
+///
+/// When the terminal reports key releases, a key repeats until it is released.
+/// Otherwise a key only counts as held once the terminal starts repeating it,
+/// and as released when those repeats stop, so auto shift never starts before
+/// the system repeat delay.
+#[derive(Debug)]
+pub struct AutoRepeat{
+    handling:Handling,
+    releases:bool,
+    /// Held movement keys. The one pressed last is the one that moves the piece.
+    shifts:Vec<Held>,
+    soft_drop:Option<Held>
+}
+
+impl AutoRepeat {
+    pub fn new(handling:Handling,releases:bool) -> Self {
+        AutoRepeat{handling, releases, shifts:Vec::new(), soft_drop:None}
+    }
+
+    /// Takes a key press, or a repeat reported by the terminal, and returns the
+    /// inputs to send right away.
+    pub fn press(&mut self,input:Input,repeat:bool) -> Vec<Input> {
+        if !matches!(input, Input::MoveLeft | Input::MoveRight | Input::SoftDrop) {
+            return if repeat { Vec::new() } else { vec![input] };
+        }
+        let releases = self.releases;
+        let held = match input {
+            Input::SoftDrop => self.soft_drop.as_mut(),
+            _ => self.shifts.iter_mut().find(|held| held.input == input)
+        };
+        if let Some(held) = held {
+            if repeat {
+                return Vec::new();
+            }
+            if !releases {
+                let quick = held.quiet <= REPEAT_TIMEOUT;
+                held.quiet = Duration::ZERO;
+                if held.confirmed || quick {
+                    held.confirmed = true;
+                    return Vec::new();
+                }
+                // 可能是系统的第一次重复，也可能是又按了一下，都只走一格
+                return vec![input];
+            }
+        }
+        match input {
+            Input::SoftDrop => self.soft_drop = Some(Held::new(input)),
+            _ => {
+                self.shifts.retain(|held| held.input != input);
+                self.shifts.push(Held::new(input));
+            }
+        }
+        vec![input]
+    }
+
+    pub fn release(&mut self,input:Input) {
+        if input == Input::SoftDrop {
+            self.soft_drop = None;
+            return;
+        }
+        let active = self.shifts.last().map(|held| held.input);
+        self.shifts.retain(|held| held.input != input);
+        if active == Some(input) {
+            if let Some(held) = self.shifts.last_mut() {
+                held.restart();
+            }
+        }
+    }
+
+    /// Forgets every held key, for when the game is paused or loses focus.
+    pub fn reset(&mut self) {
+        self.shifts.clear();
+        self.soft_drop = None;
+    }
+
+    /// Advances the held keys by `elapsed` and returns the repeated inputs that
+    /// became due. `gravity` is the current time per row, which the soft drop
+    /// speed is based on.
+    pub fn update(&mut self,elapsed:Duration,gravity:Duration) -> Vec<Input> {
+        if !self.releases {
+            self.expire(elapsed);
+        }
+        let mut inputs = Vec::new();
+        let Handling{das, arr, soft_drop_factor} = self.handling;
+        let releases = self.releases;
+        if let Some(held) = self.shifts.last_mut() {
+            held.held += elapsed;
+            if held.repeating {
+                held.carry += elapsed;
+            } else if held.held >= das && (releases || held.confirmed) {
+                // DAS 到了先走一格，之后按 ARR 走
+                held.repeating = true;
+                inputs.push(held.input);
+            }
+            if held.repeating {
+                let count = if arr.is_zero() { constant::MAX_BOARD_WIDTH } else { take_steps(&mut held.carry, arr) };
+                inputs.extend(std::iter::repeat_n(held.input, count.min(constant::MAX_BOARD_WIDTH)));
+            }
+        }
+        if let Some(held) = self.soft_drop.as_mut().filter(|held| releases || held.confirmed) {
+            held.carry += elapsed;
+            let rate = gravity / soft_drop_factor;
+            let count = if rate.is_zero() { constant::MAX_BOARD_HEIGHT } else { take_steps(&mut held.carry, rate) };
+            inputs.extend(std::iter::repeat_n(Input::SoftDrop, count.min(constant::MAX_BOARD_HEIGHT)));
+        }
+        inputs
+    }
+
+    fn expire(&mut self,elapsed:Duration) {
+        let active = self.shifts.last().map(|held| held.input);
+        for held in self.shifts.iter_mut().chain(self.soft_drop.as_mut()) {
+            held.quiet += elapsed;
+        }
+        self.shifts.retain(|held| !held.expired());
+        if self.shifts.last().map(|held| held.input) != active {
+            if let Some(held) = self.shifts.last_mut() {
+                held.restart();
+            }
+        }
+        if self.soft_drop.as_ref().is_some_and(Held::expired) {
+            self.soft_drop = None;
+        }
+    }
+}
+
+/// Takes as many whole `step`s out of `carry` as it holds and returns how many.
+fn take_steps(carry:&mut Duration,step:Duration) -> usize {
+    let count = (carry.as_nanos() / step.as_nanos()) as usize;
+    *carry -= step * count as u32;
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAVITY:Duration = Duration::from_secs(1);
+
+    fn ms(millis:u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn auto_repeat(releases:bool) -> AutoRepeat {
+        AutoRepeat::new(Handling{das:ms(100), arr:ms(20), soft_drop_factor:10}, releases)
+    }
+
+    #[test]
+    fn das_then_arr() {
+        let mut repeat = auto_repeat(true);
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![Input::MoveLeft]);
+        assert_eq!(repeat.update(ms(99), GRAVITY), vec![]);
+        // DAS 到了先走一格
+        assert_eq!(repeat.update(ms(1), GRAVITY), vec![Input::MoveLeft]);
+        assert_eq!(repeat.update(ms(19), GRAVITY), vec![]);
+        assert_eq!(repeat.update(ms(1), GRAVITY), vec![Input::MoveLeft]);
+        assert_eq!(repeat.update(ms(50), GRAVITY), vec![Input::MoveLeft; 2]);
+        assert_eq!(repeat.update(ms(10), GRAVITY), vec![Input::MoveLeft]);
+        repeat.release(Input::MoveLeft);
+        assert_eq!(repeat.update(ms(100), GRAVITY), vec![]);
+    }
+
+    #[test]
+    fn zero_arr_goes_straight_to_the_wall() {
+        let mut repeat = AutoRepeat::new(Handling{das:ms(100), arr:Duration::ZERO, soft_drop_factor:10}, true);
+        repeat.press(Input::MoveRight, false);
+        assert_eq!(repeat.update(ms(100), GRAVITY).len(), 1 + constant::MAX_BOARD_WIDTH);
+    }
+
+    #[test]
+    fn last_pressed_direction_wins() {
+        let mut repeat = auto_repeat(true);
+        repeat.press(Input::MoveLeft, false);
+        repeat.update(ms(100), GRAVITY);
+        assert_eq!(repeat.press(Input::MoveRight, false), vec![Input::MoveRight]);
+        assert_eq!(repeat.update(ms(99), GRAVITY), vec![]);
+        assert_eq!(repeat.update(ms(1), GRAVITY), vec![Input::MoveRight]);
+        // 松开右键，左键重新开始算 DAS
+        repeat.release(Input::MoveRight);
+        assert_eq!(repeat.update(ms(99), GRAVITY), vec![]);
+        assert_eq!(repeat.update(ms(1), GRAVITY), vec![Input::MoveLeft]);
+    }
+
+    #[test]
+    fn soft_drop_runs_faster_than_gravity() {
+        let mut repeat = auto_repeat(true);
+        assert_eq!(repeat.press(Input::SoftDrop, false), vec![Input::SoftDrop]);
+        assert_eq!(repeat.update(ms(250), GRAVITY), vec![Input::SoftDrop; 2]);
+        repeat.release(Input::SoftDrop);
+        assert_eq!(repeat.update(ms(250), GRAVITY), vec![]);
+    }
+
+    #[test]
+    fn without_releases_a_key_is_held_once_it_repeats() {
+        let mut repeat = auto_repeat(false);
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![Input::MoveLeft]);
+        // 系统的重复还没来，DAS 过了也不动
+        assert_eq!(repeat.update(ms(300), GRAVITY), vec![]);
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![Input::MoveLeft]);
+        assert_eq!(repeat.update(ms(30), GRAVITY), vec![]);
+        // 紧跟着的第二次重复说明键确实按着
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![]);
+        assert_eq!(repeat.update(ms(1), GRAVITY), vec![Input::MoveLeft]);
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![]);
+        assert_eq!(repeat.update(ms(20), GRAVITY), vec![Input::MoveLeft]);
+        // 重复停了 REPEAT_TIMEOUT 就当作松开
+        assert_eq!(repeat.update(REPEAT_TIMEOUT + ms(1), GRAVITY), vec![]);
+        assert!(repeat.shifts.is_empty());
+        assert_eq!(repeat.update(ms(100), GRAVITY), vec![]);
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![Input::MoveLeft]);
+    }
+
+    #[test]
+    fn without_releases_an_unrepeated_key_expires() {
+        let mut repeat = auto_repeat(false);
+        repeat.press(Input::MoveLeft, false);
+        assert_eq!(repeat.update(FIRST_REPEAT_TIMEOUT, GRAVITY), vec![]);
+        assert_eq!(repeat.shifts.len(), 1);
+        assert_eq!(repeat.update(ms(1), GRAVITY), vec![]);
+        assert!(repeat.shifts.is_empty());
+        // 已经过期，再按是新的一下，不会被当成重复
+        assert_eq!(repeat.press(Input::MoveLeft, false), vec![Input::MoveLeft]);
+        assert!(!repeat.shifts[0].confirmed);
+    }
+}
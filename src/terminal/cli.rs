@@ -0,0 +1,43 @@
+use std::ops::RangeInclusive;
+use tetris::constant::constant;
+use tetris::game::GameConfig;
+use crate::terminal::theme::Theme;
+
+pub const USAGE:&str = "Usage: tetris [--width N] [--height N] [--hidden N] [--preview N] [--theme NAME|FILE]";
+
+/// What was given on the command line.
+pub struct Options{
+    pub config:GameConfig,
+    pub theme:Theme
+}
+
+/// Reads the board options given on the command line into a [`GameConfig`],
+/// and loads the theme picked with `--theme`.
+pub fn parse_args(mut args:impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut config = GameConfig::default();
+    let mut theme = Theme::default();
+    while let Some(arg) = args.next() {
+        if arg == "--theme" {
+            let name = args.next().ok_or_else(|| format!("`{}` needs a value", arg))?;
+            theme = Theme::load(&name)?;
+            continue;
+        }
+        let (target, range) = match arg.as_str() {
+            "--width" => (&mut config.width, constant::MIN_BOARD_WIDTH ..= constant::MAX_BOARD_WIDTH),
+            "--height" => (&mut config.height, constant::MIN_BOARD_HEIGHT ..= constant::MAX_BOARD_HEIGHT),
+            "--hidden" => (&mut config.hidden, constant::MIN_HIDDEN_ROWS ..= constant::MAX_HIDDEN_ROWS),
+            "--preview" => (&mut config.preview, constant::MIN_PREVIEW ..= constant::MAX_PREVIEW),
+            _ => return Err(format!("unknown option `{}`", arg))
+        };
+        let value = args.next().ok_or_else(|| format!("`{}` needs a value", arg))?;
+        *target = parse_in(&arg, &value, range)?;
+    }
+    Ok(Options{config, theme})
+}
+
+fn parse_in(arg:&str,value:&str,range:RangeInclusive<usize>) -> Result<usize, String> {
+    match value.parse() {
+        Ok(n) if range.contains(&n) => Ok(n),
+        _ => Err(format!("`{}` expects a number from {} to {}, got `{}`", arg, range.start(), range.end(), value))
+    }
+}
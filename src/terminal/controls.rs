@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::time::Duration;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use tetris::game::Input;
+
+/// Something a key can be bound to.
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+pub enum Action{
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateClockwise,
+    RotateCounterClockwise,
+    Rotate180,
+    Hold,
+    Pause,
+    Quit
+}
+
+impl Action {
+    pub const ALL:[Action;10] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateClockwise,
+        Action::RotateCounterClockwise,
+        Action::Rotate180,
+        Action::Hold,
+        Action::Pause,
+        Action::Quit
+    ];
+
+    /// The action's key in `controls.toml`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::RotateClockwise => "rotate_cw",
+            Action::RotateCounterClockwise => "rotate_ccw",
+            Action::Rotate180 => "rotate_180",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::Quit => "quit"
+        }
+    }
+
+    /// The game input the action sends, `None` for actions the front end handles itself.
+    pub fn input(&self) -> Option<Input> {
+        match self {
+            Action::MoveLeft => Some(Input::MoveLeft),
+            Action::MoveRight => Some(Input::MoveRight),
+            Action::SoftDrop => Some(Input::SoftDrop),
+            Action::HardDrop => Some(Input::HardDrop),
+            Action::RotateClockwise => Some(Input::RotateClockwise),
+            Action::RotateCounterClockwise => Some(Input::RotateCounterClockwise),
+            Action::Rotate180 => Some(Input::Rotate180),
+            Action::Hold => Some(Input::Hold),
+            Action::Pause => Some(Input::Pause),
+            Action::Quit => None
+        }
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::MoveLeft => &["Left"],
+            Action::MoveRight => &["Right"],
+            Action::SoftDrop => &["Down"],
+            Action::HardDrop => &["Space"],
+            Action::RotateClockwise => &["Up"],
+            Action::RotateCounterClockwise => &["a"],
+            Action::Rotate180 => &["s"],
+            Action::Hold => &["h"],
+            Action::Pause => &["Esc"],
+            Action::Quit => &["Ctrl+q"]
+        }
+    }
+}
+
+/// A key together with the modifiers held with it, such as `Ctrl+q`.
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Hash)]
+pub struct Chord{
+    code:KeyCode,
+    modifiers:KeyModifiers
+}
+
+const NAMED_KEYS:[(&str,KeyCode);13] = [
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Space", KeyCode::Char(' ')),
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+];
+
+impl Chord {
+    fn new(code:KeyCode,modifiers:KeyModifiers) -> Self {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        // 字母的大小写和 Shift 是一回事，标点本身已经带着 Shift 的结果
+        match code {
+            KeyCode::Char(c) if c.is_ascii_alphabetic() => {
+                let shift = if c.is_ascii_uppercase() { KeyModifiers::SHIFT } else { KeyModifiers::NONE };
+                Chord{code:KeyCode::Char(c.to_ascii_lowercase()), modifiers:modifiers | shift}
+            }
+            KeyCode::Char(c) if c.is_ascii_punctuation() => Chord{code, modifiers:modifiers - KeyModifiers::SHIFT},
+            _ => Chord{code, modifiers}
+        }
+    }
+
+    /// The chord a key event was typed with.
+    pub fn of(key:&KeyEvent) -> Self {
+        Chord::new(key.code, key.modifiers)
+    }
+
+    /// Reads chords written like `Left`, `x`, `Shift+Space`, `Ctrl++` or `Ctrl+Alt+F1`.
+    pub fn parse(text:&str) -> Result<Self, String> {
+        // `+` 本身也可以是按键，只出现在最后
+        let (prefix, key) = match text.strip_suffix('+') {
+            Some("") => ("", "+"),
+            Some(prefix) if prefix.ends_with('+') => (&prefix[.. prefix.len() - 1], "+"),
+            _ => text.rsplit_once('+').unwrap_or(("", text))
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for part in prefix.split('+').filter(|part| !part.is_empty()) {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{}` in `{}`", part, text))
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match NAMED_KEYS.iter().find(|(name, _)| name.eq_ignore_ascii_case(key)) {
+                Some((_, code)) => *code,
+                None => match key.strip_prefix(['F', 'f']).and_then(|n| n.parse().ok()) {
+                    Some(n) if (1 ..= 12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{}` in `{}`", key, text))
+                }
+            }
+        };
+        Ok(Chord::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "Ctrl"), (KeyModifiers::ALT, "Alt"), (KeyModifiers::SHIFT, "Shift")] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match NAMED_KEYS.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => write!(f, "{}", name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{}", c),
+                KeyCode::F(n) => write!(f, "F{}", n),
+                code => write!(f, "{:?}", code)
+            }
+        }
+    }
+}
+
+/// How held movement and soft drop keys repeat.
+#[derive(Debug,Clone,Copy)]
+pub struct Handling{
+    /// Delayed Auto Shift: how long a movement key is held before it repeats.
+    pub das:Duration,
+    /// Auto Repeat Rate: the time between repeated moves, zero to move straight to the wall.
+    pub arr:Duration,
+    /// How many times faster than gravity a held soft drop moves the piece.
+    pub soft_drop_factor:u32
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Handling{
+            das:Duration::from_millis(167),
+            arr:Duration::from_millis(33),
+            soft_drop_factor:20
+        }
+    }
+}
+
+/// The key bindings and handling in use.
+#[derive(Debug,Clone)]
+pub struct Controls{
+    bindings:HashMap<Chord,Action>,
+    pub handling:Handling
+}
+
+#[derive(Deserialize,Default)]
+#[serde(default, deny_unknown_fields)]
+struct ControlsFile{
+    keys:BTreeMap<String,Vec<String>>,
+    handling:HandlingFile
+}
+
+#[derive(Deserialize,Default)]
+#[serde(default, deny_unknown_fields)]
+struct HandlingFile{
+    das:Option<u64>,
+    arr:Option<u64>,
+    soft_drop_factor:Option<u32>
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls::from_toml("").expect("default bindings are valid")
+    }
+}
+
+impl Controls {
+    /// Reads `controls.toml` from the user's config directory, falling back to
+    /// the defaults when there is none.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = dirs::config_dir().map(|dir| dir.join("tetris").join("controls.toml")) else {
+            return Ok(Controls::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Controls::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Controls::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e))
+        }
+    }
+
+    /// Builds the controls from the contents of a `controls.toml`. Actions left
+    /// out keep their default keys; a chord bound to two actions is an error.
+    pub fn from_toml(text:&str) -> Result<Self, String> {
+        let file:ControlsFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        if let Some(name) = file.keys.keys().find(|name| !Action::ALL.iter().any(|a| a.name() == name.as_str())) {
+            return Err(format!("unknown action `{}`", name));
+        }
+        let mut bindings = HashMap::new();
+        for action in Action::ALL {
+            let keys:Vec<&str> = match file.keys.get(action.name()) {
+                Some(keys) => keys.iter().map(String::as_str).collect(),
+                None => action.default_keys().to_vec()
+            };
+            if keys.is_empty() && action == Action::Quit {
+                return Err("`quit` needs at least one key".to_owned());
+            }
+            for key in keys {
+                let chord = Chord::parse(key).map_err(|e| format!("{}: {}", action.name(), e))?;
+                match bindings.insert(chord, action) {
+                    Some(other) if other != action => {
+                        return Err(format!("`{}` is bound to both {} and {}", chord, other.name(), action.name()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let defaults = Handling::default();
+        let handling = Handling{
+            das:file.handling.das.map_or(defaults.das, Duration::from_millis),
+            arr:file.handling.arr.map_or(defaults.arr, Duration::from_millis),
+            soft_drop_factor:file.handling.soft_drop_factor.unwrap_or(defaults.soft_drop_factor)
+        };
+        if handling.soft_drop_factor == 0 {
+            return Err("handling.soft_drop_factor must be at least 1".to_owned());
+        }
+        Ok(Controls{bindings, handling})
+    }
+
+    pub fn action(&self,key:&KeyEvent) -> Option<Action> {
+        self.bindings.get(&Chord::of(key)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+
+    fn key(code:KeyCode,modifiers:KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_plus_as_a_key() {
+        let plus = Chord::new(KeyCode::Char('+'), KeyModifiers::NONE);
+        assert_eq!(Chord::parse("+"), Ok(plus));
+        // Shift 已经算在标点里了
+        assert_eq!(Chord::parse("Shift++"), Ok(plus));
+        assert_eq!(Chord::parse("Ctrl++"), Ok(Chord::new(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+        assert_eq!(Chord::parse("Ctrl+Alt++"), Ok(Chord::new(KeyCode::Char('+'), KeyModifiers::CONTROL | KeyModifiers::ALT)));
+        assert!(Chord::parse("Ctrl+").is_err());
+        assert!(Chord::parse("Hyper++").is_err());
+    }
+
+    #[test]
+    fn shift_and_capitals_are_the_same_chord() {
+        let chord = Chord::parse("H").unwrap();
+        assert_eq!(Chord::parse("Shift+h"), Ok(chord));
+        assert_eq!(Chord::parse("shift+H"), Ok(chord));
+        assert_eq!(Chord::of(&key(KeyCode::Char('H'), KeyModifiers::SHIFT)), chord);
+        assert_eq!(Chord::of(&key(KeyCode::Char('H'), KeyModifiers::NONE)), chord);
+        assert_ne!(Chord::parse("h"), Ok(chord));
+        assert_eq!(chord.to_string(), "Shift+h");
+
+        let controls = Controls::from_toml("[keys]\nhold = [\"Shift+h\"]").unwrap();
+        assert_eq!(controls.action(&key(KeyCode::Char('H'), KeyModifiers::SHIFT)), Some(Action::Hold));
+        assert_eq!(controls.action(&key(KeyCode::Char('h'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn defaults_bind_every_action() {
+        let controls = Controls::default();
+        assert_eq!(controls.action(&key(KeyCode::Left, KeyModifiers::NONE)), Some(Action::MoveLeft));
+        assert_eq!(controls.action(&key(KeyCode::Char('q'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(controls.action(&key(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn rejects_a_chord_bound_twice() {
+        let text = "[keys]\nmove_left = [\"x\"]\nmove_right = [\"X\", \"Shift+x\"]\nhold = [\"x\"]";
+        assert_eq!(Controls::from_toml(text).unwrap_err(), "`x` is bound to both move_left and hold");
+        // 同一个动作写两遍不算冲突
+        assert!(Controls::from_toml("[keys]\nhold = [\"c\", \"c\"]").is_ok());
+        // 覆盖默认键的时候也会冲突
+        assert_eq!(
+            Controls::from_toml("[keys]\nhold = [\"Left\"]").unwrap_err(),
+            "`Left` is bound to both move_left and hold"
+        );
+    }
+
+    #[test]
+    fn rejects_an_unknown_action() {
+        assert_eq!(Controls::from_toml("[keys]\njump = [\"j\"]").unwrap_err(), "unknown action `jump`");
+    }
+
+    #[test]
+    fn quit_needs_a_key() {
+        assert_eq!(Controls::from_toml("[keys]\nquit = []").unwrap_err(), "`quit` needs at least one key");
+        assert!(Controls::from_toml("[keys]\npause = []").is_ok());
+    }
+
+    #[test]
+    fn soft_drop_factor_must_be_positive() {
+        assert_eq!(
+            Controls::from_toml("[handling]\nsoft_drop_factor = 0").unwrap_err(),
+            "handling.soft_drop_factor must be at least 1"
+        );
+        let controls = Controls::from_toml("[handling]\nsoft_drop_factor = 1\ndas = 100").unwrap();
+        assert_eq!(controls.handling.soft_drop_factor, 1);
+        assert_eq!(controls.handling.das, Duration::from_millis(100));
+        assert_eq!(controls.handling.arr, Handling::default().arr);
+    }
+}
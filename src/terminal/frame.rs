@@ -0,0 +1,154 @@
+use std::fmt::Display;
+use std::io::{stdout, Result, Write};
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{ContentStyle, PrintStyledContent, StyledContent};
+use crossterm::terminal::{Clear, ClearType};
+use unicode_width::UnicodeWidthChar;
+
+/// Stands in the column covered by the right half of a wide character.
+const WIDE_TAIL:char = '\0';
+
+/// One terminal column of a frame.
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+struct Cell{
+    symbol:char,
+    style:ContentStyle
+}
+
+/// How many columns the terminal draws `symbol` in, zero for control and
+/// combining characters. The Windows console draws `□` two columns wide, so it
+/// is used there without padding.
+pub fn columns(symbol:char) -> usize {
+    if cfg!(windows) && symbol == '□' { 2 } else { symbol.width().unwrap_or(0) }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell{symbol:' ', style:ContentStyle::default()}
+    }
+}
+
+/// A screenful of styled characters, drawn into before anything is written to
+/// the terminal.
+#[derive(Debug,Clone,Eq,PartialEq)]
+pub struct Frame{
+    width:u16,
+    height:u16,
+    cells:Vec<Cell>
+}
+
+impl Frame {
+    pub fn new(width:u16,height:u16) -> Self {
+        Frame{width, height, cells:vec![Cell::default(); width as usize * height as usize]}
+    }
+
+    /// Writes `content` from `(x, y)` to the right, taking as many columns as
+    /// the terminal draws each character in. Anything past the edges, and
+    /// anything that takes no column, is dropped.
+    pub fn print<D:Display + AsRef<str>>(&mut self,x:u16,y:u16,content:StyledContent<D>) {
+        if y >= self.height {
+            return;
+        }
+        let style = *content.style();
+        let row = y as usize * self.width as usize;
+        let mut column = x as usize;
+        for symbol in content.content().as_ref().chars() {
+            let width = columns(symbol);
+            // 不占列的字符没有自己的格子，丢掉
+            if width == 0 {
+                continue;
+            }
+            if column + width > self.width as usize {
+                break;
+            }
+            self.clear_wide(row, column);
+            self.clear_wide(row, column + width - 1);
+            self.cells[row + column] = Cell{symbol, style};
+            for tail in 1 .. width {
+                self.cells[row + column + tail] = Cell{symbol:WIDE_TAIL, style};
+            }
+            column += width;
+        }
+    }
+
+    fn cell(&self,x:u16,y:u16) -> Cell {
+        self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    /// How many columns the cell at `(x, y)` draws, one for the tail of a wide character.
+    fn span(&self,x:u16,y:u16) -> u16 {
+        let symbol = self.cell(x, y).symbol;
+        if symbol == WIDE_TAIL { 1 } else { columns(symbol).min((self.width - x) as usize) as u16 }
+    }
+
+    /// Blanks the wide character that `column` of the row starting at `row` is
+    /// part of, before something is written over half of it.
+    fn clear_wide(&mut self,row:usize,column:usize) {
+        let (lead, tail) = if self.cells[row + column].symbol == WIDE_TAIL {
+            (column - 1, column)
+        } else if columns(self.cells[row + column].symbol) > 1 && column + 1 < self.width as usize {
+            (column, column + 1)
+        } else {
+            return;
+        };
+        self.cells[row + lead] = Cell::default();
+        self.cells[row + tail] = Cell::default();
+    }
+}
+
+/// Writes frames to the terminal, sending only the cells that changed since
+/// the previous frame and flushing once per frame.
+#[derive(Debug,Default)]
+pub struct Renderer{
+    previous:Option<Frame>
+}
+
+impl Renderer {
+    /// Makes the next frame redraw everything, for when something else has
+    /// drawn over the screen.
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn draw(&mut self,frame:Frame) -> Result<()> {
+        let mut out = stdout().lock();
+        // 尺寸变了就整屏重画
+        let previous = self.previous.take().filter(|p| p.width == frame.width && p.height == frame.height);
+        if previous.is_none() {
+            queue!(out, Clear(ClearType::All))?;
+        }
+        // 宽字符连同它盖住的那一列一起比较、一起输出
+        let unchanged = |x:u16,y:u16,width:u16| {
+            previous.as_ref().is_some_and(|p| (x .. x + width).all(|x| p.cell(x, y) == frame.cell(x, y)))
+        };
+        for y in 0 .. frame.height {
+            let mut x = 0;
+            while x < frame.width {
+                let cell = frame.cell(x, y);
+                let width = frame.span(x, y);
+                if cell.symbol == WIDE_TAIL || unchanged(x, y, width) {
+                    x += width;
+                    continue;
+                }
+                // 同一行里连续变化、样式相同的格子一起输出
+                let start = x;
+                let mut run = String::new();
+                while x < frame.width {
+                    let next = frame.cell(x, y);
+                    let width = frame.span(x, y);
+                    if next.symbol == WIDE_TAIL || next.style != cell.style || unchanged(x, y, width) {
+                        break;
+                    }
+                    run.push(next.symbol);
+                    x += width;
+                }
+                queue!(out, MoveTo(start, y), PrintStyledContent(StyledContent::new(cell.style, run)))?;
+            }
+        }
+        queue!(out, MoveTo(0, frame.height))?;
+        out.flush()?;
+        self.previous = Some(frame);
+        Ok(())
+    }
+}
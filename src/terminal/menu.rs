@@ -0,0 +1,108 @@
+use std::io::Result;
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crate::terminal::{clear, flush_output, goto};
+
+/// The entries of the main menu, in the order they are listed.
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+pub enum MenuItem{
+    Marathon,
+    Sprint,
+    Ultra,
+    Settings,
+    HighScores,
+    Quit
+}
+
+impl MenuItem {
+    pub const ALL:[MenuItem;6] = [
+        MenuItem::Marathon,
+        MenuItem::Sprint,
+        MenuItem::Ultra,
+        MenuItem::Settings,
+        MenuItem::HighScores,
+        MenuItem::Quit
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Marathon => "Marathon",
+            MenuItem::Sprint => "Sprint",
+            MenuItem::Ultra => "Ultra",
+            MenuItem::Settings => "Settings",
+            MenuItem::HighScores => "High Scores",
+            MenuItem::Quit => "Quit"
+        }
+    }
+}
+
+/// Shows the main menu with `selected` highlighted and waits for the player to
+/// pick an entry. `notice` is printed under the entries.
+pub fn main_menu(selected:MenuItem,notice:&str) -> Result<MenuItem> {
+    let items:Vec<&str> = MenuItem::ALL.iter().map(|item| item.label()).collect();
+    let index = MenuItem::ALL.iter().position(|item| *item == selected).unwrap_or(0);
+    Ok(match select("TETRIS", &items, index, notice)? {
+        Some(index) => MenuItem::ALL[index],
+        None => MenuItem::Quit
+    })
+}
+
+/// A vertical list navigated with the arrow keys. Returns the index picked with
+/// Enter, or `None` when the player backs out with Esc or Ctrl+Q.
+pub fn select(title:&str,items:&[&str],mut index:usize,notice:&str) -> Result<Option<usize>> {
+    clear();
+    loop {
+        let _ = goto(0,0);
+        print!("{}\r\n\r\n", title.bold());
+        for (i, item) in items.iter().enumerate() {
+            if i == index {
+                print!("{}\r\n", format!("> {:<16}", item).reverse());
+            } else {
+                print!("  {:<16}\r\n", item);
+            }
+        }
+        print!("\r\n{:<40}\r\n", notice);
+        print!("{}\r\n", "↑/↓ select   Enter confirm   Esc back".dark_grey());
+        flush_output();
+        if let Event::Key(key) = read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match key.code {
+                KeyCode::Up => index = (index + items.len() - 1) % items.len(),
+                KeyCode::Down => index = (index + 1) % items.len(),
+                KeyCode::Enter => return Ok(Some(index)),
+                KeyCode::Esc => return Ok(None),
+                _ if key == KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL) => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Reads a line of text, starting from `default`. Returns `None` when the
+/// player backs out with Esc.
+pub fn prompt(title:&str,default:&str,max:usize) -> Result<Option<String>> {
+    let mut text:String = default.chars().take(max).collect();
+    loop {
+        let _ = goto(0,0);
+        print!("{}\r\n\r\n", title.bold());
+        print!("> {}{:<width$}\r\n", text, "_", width = (max + 1).saturating_sub(text.chars().count()));
+        print!("\r\n{}\r\n", "Enter confirm   Esc skip".dark_grey());
+        flush_output();
+        if let Event::Key(key) = read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match key.code {
+                KeyCode::Enter if !text.trim().is_empty() => return Ok(Some(text.trim().to_owned())),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(c) if !c.is_control() && text.chars().count() < max => text.push(c),
+                _ => {}
+            }
+        }
+    }
+}
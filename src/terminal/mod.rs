@@ -0,0 +1,166 @@
+use std::io::{stdout, Write};
+use std::time::Duration;
+use crossterm::cursor::MoveTo;
+use crossterm::execute;
+use crossterm::style::{style, Stylize};
+use crossterm::terminal::{Clear, ClearType};
+use tetris::game::{Game, GameMode};
+use tetris::model::{rotation, Board, Direction, PieceType};
+use crate::terminal::frame::{Frame, Renderer};
+use crate::terminal::scores::HighScores;
+use crate::terminal::theme::{Glyph, Theme};
+
+pub mod auto_repeat;
+pub mod cli;
+pub mod controls;
+pub mod frame;
+pub mod menu;
+pub mod scores;
+pub mod settings;
+pub mod theme;
+
+/// Columns kept for the HUD values right of the hold panel.
+const HUD_WIDTH:u16 = 12;
+
+pub trait ShowSelf{
+    fn show_self(&self,frame:&mut Frame,theme:&Theme);
+}
+
+impl ShowSelf for Game {
+    fn show_self(&self,frame:&mut Frame,theme:&Theme) {
+        // 右侧面板从画布右边两列之后开始
+        let panel_x = (self.canvas().width() * 2 + 2) as u16;
+        let hold_x = panel_x + 10;
+        let paused = self.is_paused();
+        let width = self.canvas().width();
+        let ghost = self.ghost();
+        let hidden = self.canvas().hidden();
+        let piece = self.piece();
+        for (y, line) in self.canvas().visible().enumerate() {
+            for (x, cell) in line.iter().enumerate() {
+                let (x, y) = (x as i32, y as i32);
+                let glyph = match Glyph::from(*cell) {
+                    // 暂停时盖住棋盘内容，只留下墙和底
+                    Glyph::Wall => Glyph::Wall,
+                    _ if paused => Glyph::Empty,
+                    _ if piece.in_self(x, y + hidden) => Glyph::Block(piece.r#type),
+                    Glyph::Empty if ghost.contains(&(x, y + hidden)) => Glyph::Ghost,
+                    glyph => glyph
+                };
+                frame.print(x as u16 * 2, y as u16, theme.cell(glyph));
+            }
+        }
+        if paused {
+            frame.print((width - 3) as u16, ((self.canvas().height() - hidden) / 2) as u16, "PAUSED".yellow());
+        }
+        frame.print(panel_x, 0, style("NEXT"));
+        if !paused {
+            for (i, piece_type) in self.preview().iter().enumerate() {
+                show_piece(frame, theme, *piece_type, panel_x, 1 + i as u16 * 3);
+            }
+        }
+        frame.print(hold_x, 0, style("HOLD"));
+        match self.hold() {
+            Some(piece_type) if !paused => show_piece(frame, theme, piece_type, hold_x, 1),
+            _ => {}
+        }
+        let scoring = self.scoring();
+        let hud = match self.mode() {
+            GameMode::Marathon{goal} => vec![
+                ("SCORE", scoring.score().to_string()),
+                ("LEVEL", scoring.level().to_string()),
+                ("LINES", match goal {
+                    Some(goal) => format!("{}/{}", scoring.lines().min(goal), goal),
+                    None => scoring.lines().to_string()
+                }),
+            ],
+            GameMode::Sprint{lines} => vec![
+                ("TIME", format_time(self.time())),
+                ("PPS", format!("{:.2}", self.pieces_per_second())),
+                ("LINES", format!("{}/{}", scoring.lines().min(lines), lines)),
+            ],
+            GameMode::Ultra{limit} => vec![
+                ("TIME", format_time(limit.saturating_sub(self.time()))),
+                ("SCORE", scoring.score().to_string()),
+                ("LINES", scoring.lines().to_string()),
+            ],
+        };
+        for (i, (label, value)) in hud.into_iter().enumerate() {
+            frame.print(hold_x, 4 + i as u16 * 2, style(label));
+            frame.print(hold_x, 5 + i as u16 * 2, style(value));
+        }
+    }
+}
+
+/// Draws `game` into a frame sized to fit the board and the side panels, and
+/// sends it to the terminal through `renderer`.
+pub fn render(game:&Game,theme:&Theme,renderer:&mut Renderer) -> std::io::Result<()> {
+    let canvas = game.canvas();
+    let width = canvas.width() as u16 * 2 + 2 + 10 + HUD_WIDTH;
+    let height = ((canvas.height() - canvas.hidden()) as u16).max(1 + game.preview().len() as u16 * 3).max(10);
+    let mut frame = Frame::new(width, height);
+    game.show_self(&mut frame, theme);
+    renderer.draw(frame)
+}
+
+/// Draws a piece in its spawn orientation inside a 4x2 box at `(x, y)`.
+fn show_piece(frame:&mut Frame,theme:&Theme,piece_type:PieceType,x:u16,y:u16) {
+    let shape = rotation::shape(piece_type, Direction::Up);
+    let top = shape.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
+    for (dx, dy) in shape {
+        frame.print(x + dx as u16 * 2, y + (dy - top) as u16, theme.block(piece_type));
+    }
+}
+
+/// Shows the final score and statistics once the game has ended, with the
+/// mode's high score table beside them.
+pub fn show_game_over(game:&Game,scores:&HighScores,rank:Option<usize>,notice:&str) {
+    clear();
+    let _ = goto(0,0);
+    let scoring = game.scoring();
+    let statistics = scoring.statistics();
+    let title = if game.is_completed() { "COMPLETE".green() } else { "GAME OVER".red() };
+    let lines = [
+        format!("{}", title),
+        String::new(),
+        format!("Score       {}", scoring.score()),
+        format!("Level       {}", scoring.level()),
+        format!("Lines       {}", scoring.lines()),
+        format!("Time        {}", format_time(game.time())),
+        format!("Pieces      {} ({:.2}/s)", statistics.pieces, game.pieces_per_second()),
+        String::new(),
+        format!("Singles     {}", statistics.singles),
+        format!("Doubles     {}", statistics.doubles),
+        format!("Triples     {}", statistics.triples),
+        format!("Tetrises    {}", statistics.tetrises),
+        format!("T-spins     {}", statistics.t_spins),
+        format!("B2B         {}", statistics.back_to_backs),
+        format!("Max combo   {}", statistics.max_combo),
+        String::new(),
+        notice.to_owned(),
+        "r: restart   m: menu   q: quit".to_owned(),
+    ];
+    for line in lines {
+        print!("{}\r\n", line);
+    }
+    scores::show_table(scores, game.mode(), 28, 0, rank);
+    flush_output();
+}
+
+/// Formats a duration as `m:ss.mmm`.
+pub fn format_time(time:Duration) -> String {
+    let millis = time.as_millis();
+    format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+pub fn flush_output() {
+    let _ = stdout().flush();
+}
+
+pub fn goto(x: u16, y: u16) -> std::io::Result<()> {
+    execute!(stdout(), MoveTo(x, y))
+}
+
+pub fn clear() {
+    let _ = execute!(stdout(),Clear(ClearType::All));
+}
@@ -0,0 +1,225 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+use crossterm::event::{read, Event, KeyCode, KeyEventKind};
+use crossterm::style::Stylize;
+use serde::{Deserialize, Serialize};
+use tetris::game::{Game, GameMode};
+use crate::terminal::{clear, flush_output, format_time, goto};
+
+/// How many results are kept for each mode.
+const TABLE_SIZE:usize = 10;
+
+/// One finished game in the high score table.
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub struct Score{
+    pub name:String,
+    pub score:u64,
+    pub lines:u32,
+    /// Play time in milliseconds.
+    pub time:u64,
+    /// Local date the game ended, as `YYYY-MM-DD`.
+    pub date:String
+}
+
+/// The best results per mode, kept in `scores.json` under the user's data directory.
+#[derive(Debug,Default)]
+pub struct HighScores{
+    tables:BTreeMap<String,Vec<Score>>,
+    /// Where the table is saved, `None` when it must not be written.
+    path:Option<PathBuf>
+}
+
+impl HighScores {
+    /// Every mode that has a table, in the order they are browsed.
+    pub fn modes() -> Vec<GameMode> {
+        vec![
+            GameMode::Marathon{goal:Some(150)},
+            GameMode::Marathon{goal:Some(200)},
+            GameMode::Marathon{goal:None},
+            GameMode::sprint(),
+            GameMode::ultra(2),
+            GameMode::ultra(3),
+        ]
+    }
+
+    /// Reads the saved table. A missing file gives an empty table; a file that
+    /// can't be read or parsed is reported and left untouched.
+    pub fn load() -> Result<Self> {
+        let Some(path) = dirs::data_dir().map(|dir| dir.join("tetris").join("scores.json")) else {
+            return Err(Error::new(ErrorKind::NotFound, "no data directory"));
+        };
+        let mut scores = match fs::read_to_string(&path) {
+            Ok(json) => HighScores{tables:serde_json::from_str(&json)?, path:None},
+            Err(e) if e.kind() == ErrorKind::NotFound => HighScores::default(),
+            Err(e) => return Err(e)
+        };
+        scores.path = Some(path);
+        Ok(scores)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.tables)?)
+    }
+
+    pub fn table(&self,mode:GameMode) -> &[Score] {
+        self.tables.get(&mode.to_string()).map_or(&[], |table| table.as_slice())
+    }
+
+    /// Whether a finished game earns a place in its mode's table. Sprint only
+    /// counts games that reached the goal, and games on a non-standard board
+    /// never count: the tables are kept per mode only.
+    pub fn qualifies(&self,game:&Game) -> bool {
+        if !game.is_standard() {
+            return false;
+        }
+        let score = Self::score_of(game, String::new());
+        let counts = match game.mode() {
+            GameMode::Sprint{..} => game.is_completed(),
+            _ => score.score > 0
+        };
+        let table = self.table(game.mode());
+        counts && (table.len() < TABLE_SIZE || table.iter().any(|s| rank(game.mode(), &score, s) == Ordering::Less))
+    }
+
+    /// Adds the game under `name` and returns its place in the table. Games on
+    /// a non-standard board are left out.
+    pub fn insert(&mut self,game:&Game,name:String) -> Option<usize> {
+        if !game.is_standard() {
+            return None;
+        }
+        let mode = game.mode();
+        let score = Self::score_of(game, name);
+        let table = self.tables.entry(mode.to_string()).or_default();
+        let index = table.iter().position(|s| rank(mode, &score, s) == Ordering::Less).unwrap_or(table.len());
+        table.insert(index, score);
+        table.truncate(TABLE_SIZE);
+        (index < TABLE_SIZE).then_some(index)
+    }
+
+    fn score_of(game:&Game,name:String) -> Score {
+        Score{
+            name,
+            score:game.scoring().score(),
+            lines:game.scoring().lines(),
+            time:game.time().as_millis() as u64,
+            date:chrono::Local::now().format("%Y-%m-%d").to_string()
+        }
+    }
+}
+
+/// Orders results best first: fastest time in Sprint, highest score otherwise.
+fn rank(mode:GameMode,a:&Score,b:&Score) -> Ordering {
+    match mode {
+        GameMode::Sprint{..} => a.time.cmp(&b.time),
+        _ => b.score.cmp(&a.score).then(a.time.cmp(&b.time))
+    }
+}
+
+/// Draws the table for `mode` with its top-left corner at `(x, y)`, marking the
+/// entry at `highlight`.
+pub fn show_table(scores:&HighScores,mode:GameMode,x:u16,y:u16,highlight:Option<usize>) {
+    let _ = goto(x, y);
+    print!("{}", format!("{:<52}", mode).bold());
+    let _ = goto(x, y + 1);
+    print!("{}", format!("{:<3}{:<12}{:>8}{:>6}{:>11}  {:<10}", "#", "NAME", "SCORE", "LINES", "TIME", "DATE").dark_grey());
+    let table = scores.table(mode);
+    for i in 0 .. TABLE_SIZE {
+        let _ = goto(x, y + 2 + i as u16);
+        let line = match table.get(i) {
+            Some(s) => format!(
+                "{:<3}{:<12}{:>8}{:>6}{:>11}  {:<10}",
+                i + 1,
+                s.name.chars().take(11).collect::<String>(),
+                s.score,
+                s.lines,
+                format_time(Duration::from_millis(s.time)),
+                s.date
+            ),
+            None => format!("{:<52}", "")
+        };
+        if highlight == Some(i) {
+            print!("{}", line.reverse());
+        } else {
+            print!("{}", line);
+        }
+    }
+}
+
+/// Shows the tables one mode at a time, switched with ←/→, until the player
+/// leaves with Enter or Esc.
+pub fn browse(scores:&HighScores) -> Result<()> {
+    let modes = HighScores::modes();
+    let mut index = 0;
+    clear();
+    loop {
+        let _ = goto(0,0);
+        print!("{}", "HIGH SCORES".bold());
+        show_table(scores, modes[index], 0, 2, None);
+        let _ = goto(0, 3 + TABLE_SIZE as u16);
+        print!("{}", "←/→ mode   Enter/Esc back".dark_grey());
+        flush_output();
+        if let Event::Key(key) = read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match key.code {
+                KeyCode::Left => index = (index + modes.len() - 1) % modes.len(),
+                KeyCode::Right => index = (index + 1) % modes.len(),
+                KeyCode::Enter | KeyCode::Esc => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tetris::game::{GameConfig, Input};
+    use tetris::model::Randomizer;
+
+    /// A Marathon game with some score on it.
+    fn scored(config:GameConfig) -> Game {
+        let mut game = Game::with_config(config);
+        game.handle(Input::HardDrop);
+        assert!(game.scoring().score() > 0);
+        game
+    }
+
+    #[test]
+    fn standard_boards_are_recorded() {
+        let mut scores = HighScores::default();
+        let game = scored(GameConfig::default());
+        assert!(scores.qualifies(&game));
+        assert_eq!(scores.insert(&game, "a".to_owned()), Some(0));
+        assert_eq!(scores.table(game.mode()).len(), 1);
+    }
+
+    #[test]
+    fn other_boards_stay_out_of_the_standard_table() {
+        let mut scores = HighScores::default();
+        let others = [
+            GameConfig{width:6, ..GameConfig::default()},
+            GameConfig{height:40, ..GameConfig::default()},
+            GameConfig{hidden:4, ..GameConfig::default()},
+            GameConfig{preview:1, ..GameConfig::default()},
+            GameConfig{randomizer:Randomizer::History, ..GameConfig::default()},
+        ];
+        for config in others {
+            let game = scored(config.clone());
+            assert!(!scores.qualifies(&game), "{:?}", config);
+            assert_eq!(scores.insert(&game, "a".to_owned()), None);
+            assert!(scores.table(game.mode()).is_empty());
+        }
+    }
+}
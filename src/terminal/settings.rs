@@ -0,0 +1,133 @@
+use std::io::Result;
+use crossterm::event::{read, Event, KeyCode, KeyEventKind};
+use crossterm::style::Stylize;
+use tetris::constant::constant;
+use tetris::game::{GameConfig, GameMode, GravityCurve};
+use tetris::model::Randomizer;
+use crate::terminal::{clear, flush_output, goto};
+
+const FIELDS:[&str;6] = ["Width", "Height", "Hidden rows", "Preview", "Randomizer", "Gravity"];
+const MARATHON_FIELDS:[&str;2] = ["Start level", "Goal"];
+const GOALS:[Option<u32>;3] = [Some(150), Some(200), None];
+
+/// Lets the player change the game settings with the arrow keys until they
+/// leave with Enter or Esc.
+pub fn edit(config:&mut GameConfig) -> Result<()> {
+    form("SETTINGS", &FIELDS, "Enter/Esc back", config, value, change)?;
+    Ok(())
+}
+
+/// Asks for the Marathon start level and line goal, leaving the choice in
+/// `config.start_level` and `config.mode`. Returns `false` if the player backed out.
+pub fn marathon(config:&mut GameConfig) -> Result<bool> {
+    if !matches!(config.mode, GameMode::Marathon{..}) {
+        config.mode = GameMode::marathon();
+    }
+    form("MARATHON", &MARATHON_FIELDS, "Enter start   Esc back", config, marathon_value, marathon_change)
+}
+
+/// A list of fields changed with the arrow keys. Returns `true` when left with
+/// Enter and `false` when left with Esc.
+fn form(
+    title:&str,
+    fields:&[&str],
+    hint:&str,
+    config:&mut GameConfig,
+    value:fn(&GameConfig,usize) -> String,
+    change:fn(&mut GameConfig,usize,bool)
+) -> Result<bool> {
+    let mut index = 0;
+    clear();
+    loop {
+        let _ = goto(0,0);
+        print!("{}\r\n\r\n", title.bold());
+        for (i, field) in fields.iter().enumerate() {
+            let line = format!("{:<12} < {:<10} >", field, value(config, i));
+            if i == index {
+                print!("{}\r\n", format!("> {}", line).reverse());
+            } else {
+                print!("  {}\r\n", line);
+            }
+        }
+        print!("\r\n{}\r\n", format!("↑/↓ select   ←/→ change   {}", hint).dark_grey());
+        flush_output();
+        if let Event::Key(key) = read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match key.code {
+                KeyCode::Up => index = (index + fields.len() - 1) % fields.len(),
+                KeyCode::Down => index = (index + 1) % fields.len(),
+                KeyCode::Left => change(config, index, false),
+                KeyCode::Right => change(config, index, true),
+                KeyCode::Enter => return Ok(true),
+                KeyCode::Esc => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn value(config:&GameConfig,index:usize) -> String {
+    match index {
+        0 => config.width.to_string(),
+        1 => config.height.to_string(),
+        2 => config.hidden.to_string(),
+        3 => config.preview.to_string(),
+        4 => match config.randomizer {
+            Randomizer::Random => "Random",
+            Randomizer::SevenBag => "7-bag",
+            Randomizer::History => "History"
+        }.to_owned(),
+        _ => match config.gravity {
+            GravityCurve::Guideline => "Guideline",
+            GravityCurve::Nes => "NES"
+        }.to_owned()
+    }
+}
+
+fn change(config:&mut GameConfig,index:usize,up:bool) {
+    let step = |n:usize, min:usize, max:usize| {
+        if up { (n + 1).min(max) } else { n.saturating_sub(1).max(min) }
+    };
+    match index {
+        0 => config.width = step(config.width, constant::MIN_BOARD_WIDTH, constant::MAX_BOARD_WIDTH),
+        1 => config.height = step(config.height, constant::MIN_BOARD_HEIGHT, constant::MAX_BOARD_HEIGHT),
+        2 => config.hidden = step(config.hidden, constant::MIN_HIDDEN_ROWS, constant::MAX_HIDDEN_ROWS),
+        3 => config.preview = step(config.preview, constant::MIN_PREVIEW, constant::MAX_PREVIEW),
+        4 => config.randomizer = match (config.randomizer, up) {
+            (Randomizer::Random, true) | (Randomizer::History, false) => Randomizer::SevenBag,
+            (Randomizer::SevenBag, true) | (Randomizer::Random, false) => Randomizer::History,
+            (Randomizer::History, true) | (Randomizer::SevenBag, false) => Randomizer::Random
+        },
+        _ => config.gravity = match config.gravity {
+            GravityCurve::Guideline => GravityCurve::Nes,
+            GravityCurve::Nes => GravityCurve::Guideline
+        }
+    }
+}
+
+fn marathon_value(config:&GameConfig,index:usize) -> String {
+    match (index, config.mode) {
+        (0, _) => config.start_level.to_string(),
+        (_, GameMode::Marathon{goal:Some(goal)}) => format!("{} lines", goal),
+        _ => "Endless".to_owned()
+    }
+}
+
+fn marathon_change(config:&mut GameConfig,index:usize,up:bool) {
+    if index == 0 {
+        config.start_level = if up {
+            (config.start_level + 1).min(constant::MAX_START_LEVEL)
+        } else {
+            config.start_level.saturating_sub(1).max(constant::MIN_START_LEVEL)
+        };
+        return;
+    }
+    let GameMode::Marathon{goal} = config.mode else {
+        return;
+    };
+    let current = GOALS.iter().position(|g| *g == goal).unwrap_or(0);
+    let next = if up { (current + 1) % GOALS.len() } else { (current + GOALS.len() - 1) % GOALS.len() };
+    config.mode = GameMode::Marathon{goal:GOALS[next]};
+}
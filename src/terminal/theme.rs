@@ -0,0 +1,279 @@
+use std::fs;
+use crossterm::style::{self, style, StyledContent, Stylize};
+use serde::Deserialize;
+use crate::terminal::frame;
+use tetris::enumerate::Color;
+use tetris::model::{Cell, PieceType};
+
+/// Themes that ship with the game, by name.
+const BUILT_IN:[(&str,&str);3] = [
+    ("ascii", include_str!("../../themes/ascii.toml")),
+    ("blocks", include_str!("../../themes/blocks.toml")),
+    ("high-contrast", include_str!("../../themes/high-contrast.toml")),
+];
+
+/// What a board cell is drawn as.
+#[derive(Debug,Clone,Copy,Eq,PartialEq)]
+pub enum Glyph{
+    Empty,
+    Wall,
+    Ghost,
+    Block(PieceType),
+    Garbage
+}
+
+impl From<Cell> for Glyph {
+    fn from(cell:Cell) -> Self {
+        match cell {
+            Cell::Empty => Glyph::Empty,
+            Cell::Wall => Glyph::Wall,
+            Cell::Locked(piece_type) => Glyph::Block(piece_type),
+            Cell::Garbage => Glyph::Garbage
+        }
+    }
+}
+
+/// The glyphs and colors the board is drawn with.
+#[derive(Debug,Clone)]
+pub struct Theme{
+    filled:String,
+    empty:String,
+    ghost:String,
+    wall:String,
+    /// Block colors, in the order of [`PieceType::ALL`].
+    pieces:[style::Color;7],
+    empty_color:style::Color,
+    ghost_color:style::Color,
+    wall_color:style::Color,
+    garbage_color:style::Color,
+    /// Background of every board cell, `None` to keep the terminal's own.
+    background:Option<style::Color>
+}
+
+/// The classic look, with hollow squares on the terminal's background.
+impl Default for Theme {
+    fn default() -> Self {
+        Theme{
+            filled:pad("□").unwrap_or_default(),
+            empty:"  ".to_owned(),
+            ghost:pad("□").unwrap_or_default(),
+            wall:pad("□").unwrap_or_default(),
+            pieces:PieceType::ALL.map(|p| terminal_color(p.color())),
+            empty_color:style::Color::Reset,
+            ghost_color:style::Color::DarkGrey,
+            wall_color:style::Color::Red,
+            garbage_color:style::Color::Grey,
+            background:None
+        }
+    }
+}
+
+/// Fills `glyph` out to the two columns a board cell takes: one column glyphs
+/// such as `■` get a space, two column ones such as `🟥`, or `□` on the Windows
+/// console, are kept. `None` for any other width, or for a glyph holding
+/// characters that take no column.
+fn pad(glyph:&str) -> Option<String> {
+    if glyph.chars().any(|c| frame::columns(c) == 0) {
+        return None;
+    }
+    // 每格占两列，一列宽的补一个空格
+    match glyph.chars().map(frame::columns).sum::<usize>() {
+        1 => Some(format!("{} ", glyph)),
+        2 => Some(glyph.to_owned()),
+        _ => None
+    }
+}
+
+/// The terminal color closest to one of the engine's piece colors.
+fn terminal_color(color:Color) -> style::Color {
+    match color {
+        Color::Red => style::Color::Red,
+        Color::White => style::Color::White,
+        Color::Green => style::Color::Green,
+        Color::Cyan => style::Color::Cyan,
+        Color::Yellow => style::Color::Yellow,
+        Color::Purple => style::Color::Magenta,
+        Color::Blue => style::Color::Blue,
+        // 16 色里没有橙色，用 256 色的
+        Color::Orange => style::Color::AnsiValue(208)
+    }
+}
+
+#[derive(Deserialize,Default)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile{
+    glyphs:GlyphsFile,
+    colors:ColorsFile
+}
+
+#[derive(Deserialize,Default)]
+#[serde(default, deny_unknown_fields)]
+struct GlyphsFile{
+    filled:Option<String>,
+    empty:Option<String>,
+    ghost:Option<String>,
+    wall:Option<String>
+}
+
+#[derive(Deserialize,Default)]
+#[serde(default, deny_unknown_fields)]
+struct ColorsFile{
+    i:Option<ColorValue>,
+    o:Option<ColorValue>,
+    t:Option<ColorValue>,
+    j:Option<ColorValue>,
+    l:Option<ColorValue>,
+    s:Option<ColorValue>,
+    z:Option<ColorValue>,
+    empty:Option<ColorValue>,
+    ghost:Option<ColorValue>,
+    wall:Option<ColorValue>,
+    garbage:Option<ColorValue>,
+    background:Option<ColorValue>
+}
+
+/// A color written as a name, a 256-color palette index or `#rrggbb`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorValue{
+    Index(u8),
+    Text(String)
+}
+
+impl ColorValue {
+    fn parse(&self) -> Result<style::Color, String> {
+        let text = match self {
+            ColorValue::Index(n) => return Ok(style::Color::AnsiValue(*n)),
+            ColorValue::Text(text) => text
+        };
+        if let Some(hex) = text.strip_prefix('#') {
+            let channel = |i:usize| hex.get(i .. i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(style::Color::Rgb{r, g, b}),
+                _ => Err(format!("`{}` is not a #rrggbb color", text))
+            };
+        }
+        Ok(match text.to_ascii_lowercase().replace('-', "_").as_str() {
+            "default" | "reset" => style::Color::Reset,
+            "black" => style::Color::Black,
+            "dark_grey" | "dark_gray" => style::Color::DarkGrey,
+            "red" => style::Color::Red,
+            "dark_red" => style::Color::DarkRed,
+            "green" => style::Color::Green,
+            "dark_green" => style::Color::DarkGreen,
+            "yellow" => style::Color::Yellow,
+            "dark_yellow" => style::Color::DarkYellow,
+            "blue" => style::Color::Blue,
+            "dark_blue" => style::Color::DarkBlue,
+            "magenta" | "purple" => style::Color::Magenta,
+            "dark_magenta" => style::Color::DarkMagenta,
+            "cyan" => style::Color::Cyan,
+            "dark_cyan" => style::Color::DarkCyan,
+            "white" => style::Color::White,
+            "grey" | "gray" => style::Color::Grey,
+            _ => return Err(format!("unknown color `{}`", text))
+        })
+    }
+}
+
+impl Theme {
+    /// A built-in theme by name, or a theme file by path. `classic` is the default look.
+    pub fn load(name:&str) -> Result<Self, String> {
+        if name == "classic" {
+            return Ok(Theme::default());
+        }
+        if let Some((_, text)) = BUILT_IN.iter().find(|(built_in, _)| *built_in == name) {
+            return Theme::from_toml(text);
+        }
+        let text = fs::read_to_string(name).map_err(|e| {
+            let names:Vec<&str> = BUILT_IN.iter().map(|(name, _)| *name).collect();
+            format!("{}: {} (built-in themes: classic, {})", name, e, names.join(", "))
+        })?;
+        Theme::from_toml(&text).map_err(|e| format!("{}: {}", name, e))
+    }
+
+    /// Reads a theme file. Anything it leaves out is taken from the classic theme.
+    pub fn from_toml(text:&str) -> Result<Self, String> {
+        let file:ThemeFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut theme = Theme::default();
+        let glyphs = [
+            (file.glyphs.filled, &mut theme.filled, "filled"),
+            (file.glyphs.empty, &mut theme.empty, "empty"),
+            (file.glyphs.ghost, &mut theme.ghost, "ghost"),
+            (file.glyphs.wall, &mut theme.wall, "wall"),
+        ];
+        for (value, target, name) in glyphs {
+            let Some(value) = value else {
+                continue;
+            };
+            *target = pad(&value).ok_or_else(|| format!("glyphs.{} must be one or two columns wide, got `{}`", name, value))?;
+        }
+        let colors = file.colors;
+        let pieces = [colors.i, colors.o, colors.t, colors.j, colors.l, colors.s, colors.z];
+        for (i, value) in pieces.iter().enumerate() {
+            if let Some(value) = value {
+                theme.pieces[i] = value.parse()?;
+            }
+        }
+        for (value, target) in [
+            (colors.empty, &mut theme.empty_color),
+            (colors.ghost, &mut theme.ghost_color),
+            (colors.wall, &mut theme.wall_color),
+            (colors.garbage, &mut theme.garbage_color),
+        ] {
+            if let Some(value) = value {
+                *target = value.parse()?;
+            }
+        }
+        if let Some(value) = colors.background {
+            theme.background = Some(value.parse()?);
+        }
+        Ok(theme)
+    }
+
+    /// A board cell, with the board background.
+    pub fn cell(&self,glyph:Glyph) -> StyledContent<&str> {
+        let (text, color) = match glyph {
+            Glyph::Empty => (&self.empty, self.empty_color),
+            Glyph::Wall => (&self.wall, self.wall_color),
+            Glyph::Ghost => (&self.ghost, self.ghost_color),
+            Glyph::Block(piece_type) => (&self.filled, self.piece_color(piece_type)),
+            Glyph::Garbage => (&self.filled, self.garbage_color)
+        };
+        let content = style(text.as_str()).with(color);
+        match self.background {
+            Some(background) => content.on(background),
+            None => content
+        }
+    }
+
+    /// A block of a piece shown outside the board, in the preview and hold panels.
+    pub fn block(&self,piece_type:PieceType) -> StyledContent<&str> {
+        style(self.filled.as_str()).with(self.piece_color(piece_type))
+    }
+
+    fn piece_color(&self,piece_type:PieceType) -> style::Color {
+        let index = PieceType::ALL.iter().position(|p| *p == piece_type).unwrap_or(0);
+        self.pieces[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_glyphs_to_two_columns() {
+        assert_eq!(pad("■"), Some("■ ".to_owned()));
+        assert_eq!(pad("[]"), Some("[]".to_owned()));
+        assert_eq!(pad("🟥"), Some("🟥".to_owned()));
+        assert_eq!(pad("中"), Some("中".to_owned()));
+    }
+
+    #[test]
+    fn rejects_glyphs_that_do_not_fit() {
+        for glyph in ["", "abc", "中a", "🟥🟥", "e\u{301}", "\t", "\u{200b}"] {
+            assert_eq!(pad(glyph), None, "{:?}", glyph);
+        }
+    }
+}